@@ -0,0 +1,109 @@
+use std::borrow::Cow;
+
+/// Currency symbols, with the longer ones that end in another symbol first.
+const SYMBOLS: [&str; 16] = [
+    "US$", "A$", "C$", "R$", "$", "€", "£", "¥", "₹", "₩", "₽", "₺", "₪", "¢", "kr", "zł",
+];
+
+/// ISO 4217 codes of common currencies.
+const CODES: [&str; 24] = [
+    "USD", "EUR", "GBP", "JPY", "CNY", "CHF", "CAD", "AUD", "NZD", "HKD", "SGD", "SEK", "NOK",
+    "DKK", "PLN", "CZK", "HUF", "INR", "BRL", "MXN", "ZAR", "KRW", "RUB", "TRY",
+];
+
+/// Strips accounting notation from `s`: negatives in parentheses like `(1,234.00)`, trailing
+/// minus signs like `1,234.00-`, and leading or trailing currency symbols and codes like `$5` or
+/// `5 EUR`. Returns the plain number, and a note about each thing that was stripped.
+pub fn strip(s: &str) -> (Cow<'_, str>, Vec<&'static str>) {
+    let mut notes = Vec::new();
+    let mut s = s;
+    let mut negative = false;
+    if let Some(inner) = s.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+        s = inner.trim();
+        negative = true;
+        notes.push("Read parentheses as negative");
+    }
+    let (sign, rest) = match s.strip_prefix(['-', '+']) {
+        Some(rest) => (&s[..1], rest),
+        None => ("", s),
+    };
+    let mut currency = false;
+    // A sign can come before the currency, like `-$5`.
+    if let Some(rest) = strip_currency(rest, str::strip_prefix) {
+        s = rest.trim_start();
+        currency = true;
+        negative ^= sign == "-";
+    }
+    if let Some(rest) = strip_currency(s, str::strip_suffix) {
+        s = rest.trim_end();
+        currency = true;
+    }
+    if currency {
+        notes.push("Stripped currency");
+    }
+    if let Some(rest) = s.strip_suffix('-') {
+        if rest.ends_with(|c: char| c.is_ascii_digit() || c == '.' || c == ',') {
+            s = rest;
+            negative = !negative;
+            notes.push("Read trailing minus as negative");
+        }
+    }
+    if notes.is_empty() {
+        return (Cow::Borrowed(s), notes);
+    }
+    let s = match (negative, s.strip_prefix('-')) {
+        (true, Some(s)) => s.to_string(),
+        (true, None) => format!("-{s}"),
+        (false, _) => s.to_string(),
+    };
+    (Cow::Owned(s), notes)
+}
+
+/// Strips a currency symbol or code from `s` with `strip`, which strips a prefix or a suffix.
+/// Codes must be separated from the number, so `5USDT` is left alone.
+fn strip_currency<'a>(
+    s: &'a str,
+    strip: impl Fn(&'a str, &'static str) -> Option<&'a str>,
+) -> Option<&'a str> {
+    SYMBOLS
+        .iter()
+        .find_map(|symbol| strip(s, symbol))
+        .or_else(|| {
+            CODES.iter().filter_map(|code| strip(s, code)).find(|rest| {
+                !rest.starts_with(char::is_alphabetic) && !rest.ends_with(char::is_alphabetic)
+            })
+        })
+        .filter(|rest| !rest.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip_str(s: &str) -> String {
+        strip(s).0.into_owned()
+    }
+
+    #[test]
+    fn strip_works() {
+        assert_eq!(strip_str("(1,234.00)"), "-1,234.00");
+        assert_eq!(strip_str("1,234.00-"), "-1,234.00");
+        assert_eq!(strip_str("$5"), "5");
+        assert_eq!(strip_str("-$5"), "-5");
+        assert_eq!(strip_str("($5.00)"), "-5.00");
+        assert_eq!(strip_str("US$ 12"), "12");
+        assert_eq!(strip_str("12 €"), "12");
+        assert_eq!(strip_str("USD 1,234.00-"), "-1,234.00");
+        assert_eq!(strip_str("5 EUR"), "5");
+        assert_eq!(strip_str("(-5)"), "5");
+        assert_eq!(strip_str("5USDT"), "5USDT");
+        assert_eq!(strip_str("$"), "$");
+        assert_eq!(strip_str("-"), "-");
+        assert_eq!(strip_str("1m"), "1m");
+        assert_eq!(strip("42").1, Vec::<&str>::new());
+        assert_eq!(
+            strip("(€5)").1,
+            ["Read parentheses as negative", "Stripped currency"]
+        );
+    }
+}
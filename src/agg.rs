@@ -0,0 +1,293 @@
+use crate::{Sum, Summary};
+use std::fmt;
+use std::str::FromStr;
+
+/// An aggregation to compute over the values that were parsed from the input. Some of them
+/// need the [`Summer`](crate::Summer) to track more than the sum, see [`Agg::needs_stats`] and
+/// [`Agg::needs_quantiles`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Agg {
+    /// The sum of all values.
+    Sum,
+    /// The number of successfully parsed values.
+    Count,
+    /// The arithmetic mean of the parsed values, as a Float.
+    Mean,
+    /// The smallest parsed value.
+    Min,
+    /// The largest parsed value.
+    Max,
+    /// The variance of the parsed values, treating them as a sample.
+    Variance,
+    /// The variance of the parsed values, treating them as the whole population.
+    PopulationVariance,
+    /// The standard deviation of the parsed values, treating them as a sample.
+    StdDev,
+    /// The standard deviation of the parsed values, treating them as the whole population.
+    PopulationStdDev,
+    /// The standard error of the mean, which is the sample standard deviation divided by the
+    /// square root of the count.
+    StdErr,
+    /// The median of the parsed values, which is the same as `p50` but labeled `median`.
+    /// Requires the [`Summer`](crate::Summer) to track quantiles.
+    Median,
+    /// The given percentile (between 0 and 100) of the parsed values, e.g. `p99.9`. Requires the
+    /// [`Summer`](crate::Summer) to track quantiles.
+    Percentile(f64),
+}
+
+impl Agg {
+    /// Computes this aggregation from `summary`. Returns `None` if there were no values to
+    /// aggregate.
+    pub fn compute(&self, summary: &Summary) -> Option<Sum> {
+        match self {
+            Agg::Sum => Some(summary.sum.clone()),
+            Agg::Count => Some(Sum::Integer(summary.count() as i128)),
+            Agg::Mean => match summary.count() {
+                0 => None,
+                n => Some(Sum::Float(summary.sum.to_f64() / n as f64)),
+            },
+            Agg::Min => summary.min.clone(),
+            Agg::Max => summary.max.clone(),
+            Agg::Variance => summary.moments.variance(true).map(Sum::Float),
+            Agg::PopulationVariance => summary.moments.variance(false).map(Sum::Float),
+            Agg::StdDev => summary.moments.variance(true).map(|v| Sum::Float(v.sqrt())),
+            Agg::PopulationStdDev => summary
+                .moments
+                .variance(false)
+                .map(|v| Sum::Float(v.sqrt())),
+            Agg::StdErr => summary
+                .moments
+                .variance(true)
+                .map(|v| Sum::Float((v / summary.moments.count as f64).sqrt())),
+            Agg::Median => summary.quantiles.as_ref()?.percentile(50.0),
+            Agg::Percentile(p) => summary.quantiles.as_ref()?.percentile(*p),
+        }
+    }
+
+    /// Whether computing this aggregation requires the [`Summer`](crate::Summer) to track stats.
+    pub fn needs_stats(&self) -> bool {
+        matches!(
+            self,
+            Agg::Min
+                | Agg::Max
+                | Agg::Variance
+                | Agg::PopulationVariance
+                | Agg::StdDev
+                | Agg::PopulationStdDev
+                | Agg::StdErr
+        )
+    }
+
+    /// Whether computing this aggregation requires the [`Summer`](crate::Summer) to track
+    /// quantiles.
+    pub fn needs_quantiles(&self) -> bool {
+        matches!(self, Agg::Median | Agg::Percentile(_))
+    }
+}
+
+/// Tracks the mean and variance of a stream of values in a single pass, using Welford's
+/// numerically stable online algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Moments {
+    count: u64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Moments {
+    pub fn add(&mut self, x: f64) {
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    /// Returns the sample variance if `sample` is true, otherwise the population variance.
+    /// Returns `None` if there are too few values.
+    pub fn variance(&self, sample: bool) -> Option<f64> {
+        let n = if sample {
+            self.count.checked_sub(1)?
+        } else {
+            self.count
+        };
+        (n > 0).then(|| self.m2 / n as f64)
+    }
+}
+
+impl FromStr for Agg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sum" => Ok(Agg::Sum),
+            "count" => Ok(Agg::Count),
+            "mean" => Ok(Agg::Mean),
+            "min" => Ok(Agg::Min),
+            "max" => Ok(Agg::Max),
+            "variance" => Ok(Agg::Variance),
+            "pvariance" => Ok(Agg::PopulationVariance),
+            "stddev" => Ok(Agg::StdDev),
+            "pstddev" => Ok(Agg::PopulationStdDev),
+            "stderr" => Ok(Agg::StdErr),
+            "median" => Ok(Agg::Median),
+            _ => match s.strip_prefix('p').map(str::parse::<f64>) {
+                Some(Ok(p)) if (0.0..=100.0).contains(&p) => Ok(Agg::Percentile(p)),
+                Some(_) => Err(format!(
+                    "invalid percentile {s:?} (expected p0 to p100, e.g. p99.9)"
+                )),
+                None => Err(format!(
+                    "unknown aggregation {s:?} (expected one of sum, count, mean, min, max, \
+                     variance, pvariance, stddev, pstddev, stderr, median, pN)"
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Agg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Agg::Sum => write!(f, "sum"),
+            Agg::Count => write!(f, "count"),
+            Agg::Mean => write!(f, "mean"),
+            Agg::Min => write!(f, "min"),
+            Agg::Max => write!(f, "max"),
+            Agg::Variance => write!(f, "variance"),
+            Agg::PopulationVariance => write!(f, "pvariance"),
+            Agg::StdDev => write!(f, "stddev"),
+            Agg::PopulationStdDev => write!(f, "pstddev"),
+            Agg::StdErr => write!(f, "stderr"),
+            Agg::Median => write!(f, "median"),
+            Agg::Percentile(p) => write!(f, "p{p}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{QuantileMode, Summer};
+
+    #[test]
+    fn parse_works() {
+        assert_eq!("mean".parse(), Ok(Agg::Mean));
+        assert_eq!("median".parse(), Ok(Agg::Median));
+        assert_eq!("p99.9".parse(), Ok(Agg::Percentile(99.9)));
+        assert!("p101".parse::<Agg>().is_err());
+        assert!("pNaN".parse::<Agg>().is_err());
+        assert!("mode".parse::<Agg>().is_err());
+        for agg in [
+            Agg::Sum,
+            Agg::Count,
+            Agg::Mean,
+            Agg::Min,
+            Agg::Max,
+            Agg::Variance,
+            Agg::PopulationVariance,
+            Agg::StdDev,
+            Agg::PopulationStdDev,
+            Agg::StdErr,
+            Agg::Median,
+            Agg::Percentile(50.0),
+            Agg::Percentile(0.5),
+        ] {
+            assert_eq!(agg.to_string().parse(), Ok(agg));
+        }
+    }
+
+    #[test]
+    fn compute_works() {
+        let mut summer = Summer::builder().stats(true).build();
+        summer.feed_reader("3\n1.5\nOOPS\n-2\n".as_bytes()).unwrap();
+        let summary = summer.summary();
+        assert_eq!(Agg::Sum.compute(summary), Some(Sum::Float(2.5)));
+        assert_eq!(Agg::Count.compute(summary), Some(Sum::Integer(3)));
+        assert_eq!(Agg::Min.compute(summary), Some(Sum::Integer(-2)));
+        assert_eq!(Agg::Max.compute(summary), Some(Sum::Integer(3)));
+        assert_eq!(Agg::Mean.compute(summary), Some(Sum::Float(2.5 / 3.0)));
+
+        // Stats aren't tracked unless they're asked for.
+        let mut summer = Summer::builder().build();
+        summer.feed_line("1");
+        assert_eq!(Agg::Min.compute(summer.summary()), None);
+        assert_eq!(Agg::StdDev.compute(summer.summary()), None);
+    }
+
+    #[test]
+    fn compute_variance() {
+        let mut summer = Summer::builder().stats(true).build();
+        summer
+            .feed_reader("2\n4\n4\n4\n5\n5\n7\n9\n".as_bytes())
+            .unwrap();
+        let summary = summer.summary();
+        assert_eq!(
+            Agg::PopulationVariance.compute(summary),
+            Some(Sum::Float(4.0))
+        );
+        assert_eq!(
+            Agg::PopulationStdDev.compute(summary),
+            Some(Sum::Float(2.0))
+        );
+        assert_eq!(Agg::Variance.compute(summary), Some(Sum::Float(32.0 / 7.0)));
+        assert_eq!(
+            Agg::StdDev.compute(summary),
+            Some(Sum::Float((32.0f64 / 7.0).sqrt()))
+        );
+        assert_eq!(
+            Agg::StdErr.compute(summary),
+            Some(Sum::Float((32.0f64 / 7.0 / 8.0).sqrt()))
+        );
+    }
+
+    #[test]
+    fn moments_are_stable() {
+        // The naive sum-of-squares formula loses all precision with a large offset.
+        let mut moments = Moments::default();
+        for x in [4.0, 7.0, 13.0, 16.0] {
+            moments.add(1e9 + x);
+        }
+        assert_eq!(moments.variance(true), Some(30.0));
+    }
+
+    #[test]
+    fn compute_percentile() {
+        let mut summer = Summer::builder().build();
+        summer.feed_line("1");
+        assert_eq!(Agg::Percentile(50.0).compute(summer.summary()), None);
+
+        let mut summer = Summer::builder()
+            .quantiles(Some(QuantileMode::Exact))
+            .build();
+        summer.feed_reader("3\n1\nOOPS\n2\n".as_bytes()).unwrap();
+        let summary = summer.summary();
+        assert_eq!(
+            Agg::Percentile(50.0).compute(summary),
+            Some(Sum::Integer(2))
+        );
+        assert_eq!(Agg::Median.compute(summary), Some(Sum::Integer(2)));
+        assert_eq!(
+            Agg::Percentile(100.0).compute(summary),
+            Some(Sum::Integer(3))
+        );
+        summer.finish();
+        assert_eq!(Agg::Median.compute(summer.summary()), Some(Sum::Integer(2)));
+    }
+
+    #[test]
+    fn compute_empty() {
+        let summary = Summary::default();
+        assert_eq!(Agg::Count.compute(&summary), Some(Sum::Integer(0)));
+        assert_eq!(Agg::Mean.compute(&summary), None);
+        assert_eq!(Agg::Min.compute(&summary), None);
+        assert_eq!(Agg::PopulationVariance.compute(&summary), None);
+
+        let mut summer = Summer::builder().stats(true).build();
+        summer.feed_line("1");
+        assert_eq!(
+            Agg::PopulationVariance.compute(summer.summary()),
+            Some(Sum::Float(0.0))
+        );
+        assert_eq!(Agg::Variance.compute(summer.summary()), None);
+    }
+}
@@ -0,0 +1,176 @@
+use num_bigint::BigInt;
+use num_traits::Signed;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul};
+
+/// An exact decimal number, stored as an unscaled integer and the number of digits after the
+/// decimal point. Adding two Decimals keeps the larger scale, so `1.10 + 2.2` is `3.30`.
+/// Comparisons are by value, so `1.10 == 1.1`; compare [`Decimal::scale`] to tell them apart.
+#[derive(Clone)]
+pub struct Decimal {
+    unscaled: BigInt,
+    scale: u32,
+}
+
+impl Decimal {
+    pub fn new(unscaled: impl Into<BigInt>, scale: u32) -> Self {
+        Decimal {
+            unscaled: unscaled.into(),
+            scale,
+        }
+    }
+
+    /// Parses a plain decimal number like `-1.10`, keeping its scale. Returns `None` for anything
+    /// else, including integers without a decimal point and scientific notation.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (int, frac) = s.split_once('.')?;
+        let digits = int.trim_start_matches(['-', '+']);
+        if int.len() - digits.len() > 1
+            || digits.len() + frac.len() == 0
+            || !digits
+                .bytes()
+                .chain(frac.bytes())
+                .all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+        let unscaled: BigInt = format!("{int}{frac}").parse().ok()?;
+        Some(Decimal::new(unscaled, frac.len().try_into().ok()?))
+    }
+
+    /// The number of digits after the decimal point.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Returns this value with at least `scale` digits after the decimal point.
+    fn rescale(&self, scale: u32) -> BigInt {
+        match scale.cmp(&self.scale) {
+            Ordering::Greater => &self.unscaled * BigInt::from(10).pow(scale - self.scale),
+            _ => self.unscaled.clone(),
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+}
+
+impl From<BigInt> for Decimal {
+    fn from(n: BigInt) -> Self {
+        Decimal::new(n, 0)
+    }
+}
+
+impl Add for Decimal {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let scale = self.scale.max(other.scale);
+        Decimal::new(self.rescale(scale) + other.rescale(scale), scale)
+    }
+}
+
+impl Mul for Decimal {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Decimal::new(self.unscaled * other.unscaled, self.scale + other.scale)
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        self.rescale(scale).cmp(&other.rescale(scale))
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scale = self.scale as usize;
+        let digits = self.unscaled.abs().to_string();
+        let digits = format!("{digits:0>width$}", width = scale + 1);
+        let (int, frac) = digits.split_at(digits.len() - scale);
+        let sign = if self.unscaled.is_negative() { "-" } else { "" };
+        if frac.is_empty() {
+            write!(f, "{sign}{int}")
+        } else {
+            write!(f, "{sign}{int}.{frac}")
+        }
+    }
+}
+
+impl fmt::Debug for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_works() {
+        let parse = |s| Decimal::parse(s).map(|n| n.to_string());
+        assert_eq!(parse("1.10").as_deref(), Some("1.10"));
+        assert_eq!(parse("-0.05").as_deref(), Some("-0.05"));
+        assert_eq!(parse("+.5").as_deref(), Some("0.5"));
+        assert_eq!(parse("3.").as_deref(), Some("3"));
+        assert_eq!(Decimal::parse("3"), None);
+        assert_eq!(Decimal::parse("."), None);
+        assert_eq!(Decimal::parse("--1.0"), None);
+        assert_eq!(Decimal::parse("1e3.0"), None);
+    }
+
+    #[test]
+    fn add_keeps_largest_scale() {
+        let sum = Decimal::parse("1.10").unwrap() + Decimal::parse("2.2").unwrap();
+        assert_eq!(sum.to_string(), "3.30");
+        let sum = sum + Decimal::from(BigInt::from(-4));
+        assert_eq!(sum.to_string(), "-0.70");
+    }
+
+    #[test]
+    fn mul_adds_scales() {
+        let product = Decimal::parse("1.5").unwrap() * Decimal::parse("-0.25").unwrap();
+        assert_eq!(product.to_string(), "-0.375");
+        let product = Decimal::parse("1.5").unwrap() * Decimal::from(BigInt::from(1024));
+        assert_eq!(product.to_string(), "1536.0");
+    }
+
+    #[test]
+    fn cmp_ignores_scale() {
+        assert!(Decimal::new(110, 2) < Decimal::new(12, 1));
+        assert!(Decimal::new(-1, 0) < Decimal::new(-5, 1));
+        assert_eq!(
+            Decimal::new(110, 2).cmp(&Decimal::new(11, 1)),
+            Ordering::Equal
+        );
+        assert_eq!(Decimal::new(110, 2), Decimal::new(11, 1));
+        assert_ne!(Decimal::new(110, 2).scale(), Decimal::new(11, 1).scale());
+    }
+
+    #[test]
+    fn display_pads_leading_zeros() {
+        assert_eq!(Decimal::new(5, 3).to_string(), "0.005");
+        assert_eq!(Decimal::new(-5, 3).to_string(), "-0.005");
+        assert_eq!(Decimal::new(42, 0).to_string(), "42");
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A kind of problem with the input, counted by [`Diagnostics`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Problem {
+    /// A line, field or JSON value that was skipped because it was missing or invalid.
+    Skipped,
+    /// A value that failed to parse, and was counted as 0.
+    ParseFailure,
+    /// A value that had commas stripped, assuming they separate thousands.
+    StrippedCommas,
+    /// A value that couldn't be summed exactly with `exact`, and was summed as a float instead.
+    FloatFallback,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Problem::Skipped => "skipped (missing field or invalid input)",
+            Problem::ParseFailure => "failed to parse, counted as 0",
+            Problem::StrippedCommas => {
+                "had commas stripped (use --locale if they're decimal points)"
+            }
+            Problem::FloatFallback => "summed as floats instead of exactly",
+        })
+    }
+}
+
+/// How many times a [`Problem`] happened, with the text of the first few times.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub count: usize,
+    pub samples: Vec<String>,
+}
+
+/// Counts the [`Problem`]s with the input, keeping a few samples of each, so they can be
+/// reported once at the end rather than as a warning for each one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    tallies: BTreeMap<Problem, Tally>,
+}
+
+impl Diagnostics {
+    /// The number of samples kept for each problem.
+    pub const SAMPLES: usize = 3;
+
+    /// Counts an instance of `problem`, where `sample` is the offending line or value.
+    pub fn record(&mut self, problem: Problem, sample: &str) {
+        let tally = self.tallies.entry(problem).or_default();
+        tally.count += 1;
+        if tally.samples.len() < Self::SAMPLES {
+            tally.samples.push(sample.to_string());
+        }
+    }
+
+    /// The tally of `problem`, if it happened at all.
+    pub fn get(&self, problem: Problem) -> Option<&Tally> {
+        self.tallies.get(&problem)
+    }
+
+    /// Each problem that happened, in the order they're declared in [`Problem`].
+    pub fn iter(&self) -> impl Iterator<Item = (Problem, &Tally)> {
+        self.tallies
+            .iter()
+            .map(|(&problem, tally)| (problem, tally))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tallies.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_keeps_samples() {
+        let mut diagnostics = Diagnostics::default();
+        assert!(diagnostics.is_empty());
+        for sample in ["a", "b", "c", "d"] {
+            diagnostics.record(Problem::ParseFailure, sample);
+        }
+        diagnostics.record(Problem::Skipped, "x");
+        assert_eq!(
+            diagnostics.get(Problem::ParseFailure),
+            Some(&Tally {
+                count: 4,
+                samples: vec!["a".into(), "b".into(), "c".into()],
+            })
+        );
+        assert_eq!(diagnostics.get(Problem::FloatFallback), None);
+        let problems: Vec<_> = diagnostics.iter().map(|(problem, _)| problem).collect();
+        assert_eq!(problems, [Problem::Skipped, Problem::ParseFailure]);
+    }
+}
@@ -0,0 +1,160 @@
+use fs_err::File;
+use std::fs::Metadata;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+
+/// Reads the lines of a file as they're appended to it, like `tail -f`. When the file is
+/// rotated, the rest of the old file is read before following the new one from its start, and
+/// when it's truncated, it's followed from its start again.
+pub struct Follower {
+    path: PathBuf,
+    reader: BufReader<File>,
+    metadata: Metadata,
+    /// The offset just past what's been read.
+    pos: u64,
+    /// The number of lines finished in the file being followed.
+    line: u64,
+    /// The start of a line that hasn't been finished yet.
+    partial: String,
+}
+
+impl Follower {
+    /// Opens `path` to follow it from its start.
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let file = File::open(&path)?;
+        Ok(Follower {
+            metadata: file.metadata()?,
+            reader: BufReader::new(file),
+            path,
+            pos: 0,
+            line: 0,
+            partial: String::new(),
+        })
+    }
+
+    /// Returns the lines that have been finished since the last poll, without their line
+    /// endings, each with its 1-based line number in the file it was read from. Doesn't wait for
+    /// more lines, so it may return none.
+    pub fn poll(&mut self) -> io::Result<Vec<(u64, String)>> {
+        let mut lines = Vec::new();
+        self.read_lines(&mut lines)?;
+        let metadata = match fs_err::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            // Rotated, but the new file hasn't been created yet.
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(lines),
+            Err(err) => return Err(err),
+        };
+        if !same_file(&metadata, &self.metadata) {
+            tracing::info!(path = ?self.path, "File was rotated, following the new one");
+            // The old file won't be finished, so its last line is as complete as it'll get.
+            if !self.partial.is_empty() {
+                lines.push((self.line + 1, std::mem::take(&mut self.partial)));
+            }
+        } else if metadata.len() < self.pos {
+            tracing::info!(path = ?self.path, "File was truncated, following it from the start");
+            self.partial.clear();
+        } else {
+            return Ok(lines);
+        }
+        // Reopen the path rather than seeking, in case a rotation looked like a truncation.
+        let Ok(file) = File::open(&self.path) else {
+            return Ok(lines);
+        };
+        self.metadata = file.metadata()?;
+        self.reader = BufReader::new(file);
+        self.pos = 0;
+        self.line = 0;
+        self.read_lines(&mut lines)?;
+        Ok(lines)
+    }
+
+    /// Reads to the end of the file, adding each finished line to `lines`.
+    fn read_lines(&mut self, lines: &mut Vec<(u64, String)>) -> io::Result<()> {
+        loop {
+            let n = self.reader.read_line(&mut self.partial)?;
+            if n == 0 {
+                return Ok(());
+            }
+            self.pos += n as u64;
+            if self.partial.ends_with('\n') {
+                let line = self.partial.trim_end_matches(['\n', '\r']);
+                self.line += 1;
+                lines.push((self.line, line.to_string()));
+                self.partial.clear();
+            }
+        }
+    }
+}
+
+/// Whether `a` and `b` are the metadata of the same file, rather than one that replaced it.
+#[cfg(unix)]
+fn same_file(a: &Metadata, b: &Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    a.dev() == b.dev() && a.ino() == b.ino()
+}
+
+/// Whether `a` and `b` are the metadata of the same file. There's no stable way to tell here,
+/// so a rotation is only noticed when the new file is shorter, and is then read like a truncation.
+#[cfg(not(unix))]
+fn same_file(_: &Metadata, _: &Metadata) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn append(path: &std::path::Path, s: &str) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(s.as_bytes()).unwrap();
+    }
+
+    /// Polls `follower`, returning the lines in the format `line:text`.
+    fn poll(follower: &mut Follower) -> Vec<String> {
+        let lines = follower.poll().unwrap();
+        lines.into_iter().map(|(n, s)| format!("{n}:{s}")).collect()
+    }
+
+    #[test]
+    fn follow_works() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log");
+        append(&path, "1\n2\r\n");
+        let mut follower = Follower::open(&path).unwrap();
+        assert_eq!(poll(&mut follower), ["1:1", "2:2"]);
+        assert!(poll(&mut follower).is_empty());
+
+        // Partial lines wait to be finished.
+        append(&path, "3");
+        assert!(poll(&mut follower).is_empty());
+        append(&path, "4\n5\n");
+        assert_eq!(poll(&mut follower), ["3:34", "4:5"]);
+
+        // Truncated, so the lines are numbered from the start again.
+        std::fs::write(&path, "6\n").unwrap();
+        assert_eq!(poll(&mut follower), ["1:6"]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn follow_rotation_works() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log");
+        append(&path, "6\n");
+        let mut follower = Follower::open(&path).unwrap();
+        assert_eq!(poll(&mut follower), ["1:6"]);
+
+        // Rotated, with a line written to the old file first.
+        append(&path, "7");
+        std::fs::rename(&path, dir.path().join("log.1")).unwrap();
+        assert!(poll(&mut follower).is_empty());
+        append(&path, "8\n");
+        assert_eq!(poll(&mut follower), ["2:7", "1:8"]);
+    }
+}
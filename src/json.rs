@@ -0,0 +1,123 @@
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+/// A path that selects values from JSON, like `.response.bytes`, `.items[0]` or
+/// `.items[].price`. `[]` selects every element of an array, or every value of an object, so a
+/// path can select more than one value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    path: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+    Each,
+}
+
+impl JsonPath {
+    /// Returns the values in `value` that this path selects.
+    pub fn select<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        let mut selected = vec![value];
+        for segment in &self.segments {
+            selected = selected
+                .into_iter()
+                .flat_map(|value| match (segment, value) {
+                    (Segment::Key(key), _) => value.get(key).into_iter().collect(),
+                    (Segment::Index(i), _) => value.get(i).into_iter().collect(),
+                    (Segment::Each, Value::Array(values)) => values.iter().collect(),
+                    (Segment::Each, Value::Object(values)) => values.values().collect(),
+                    (Segment::Each, _) => Vec::new(),
+                })
+                .collect();
+        }
+        selected
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some(mut rest) = s.strip_prefix('.') else {
+            return Err(format!("JSON path {s:?} must start with '.'"));
+        };
+        let mut segments = Vec::new();
+        loop {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            let (key, tail) = rest.split_at(end);
+            if !key.is_empty() {
+                segments.push(Segment::Key(key.to_string()));
+            }
+            rest = tail;
+            while let Some(tail) = rest.strip_prefix('[') {
+                let Some((index, tail)) = tail.split_once(']') else {
+                    return Err(format!("unclosed '[' in JSON path {s:?}"));
+                };
+                segments.push(match index {
+                    "" => Segment::Each,
+                    i => Segment::Index(
+                        i.parse()
+                            .map_err(|_| format!("invalid index {i:?} in JSON path {s:?}"))?,
+                    ),
+                });
+                rest = tail;
+            }
+            match rest.strip_prefix('.') {
+                Some(tail) => rest = tail,
+                None if rest.is_empty() => break,
+                None => return Err(format!("invalid JSON path {s:?}")),
+            }
+        }
+        Ok(JsonPath {
+            path: s.to_string(),
+            segments,
+        })
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn select(path: &str, value: &Value) -> Vec<Value> {
+        let path: JsonPath = path.parse().unwrap();
+        path.select(value).into_iter().cloned().collect()
+    }
+
+    #[test]
+    fn parse_works() {
+        assert!(".".parse::<JsonPath>().is_ok());
+        assert!(".a.b[0][].c".parse::<JsonPath>().is_ok());
+        assert!("a.b".parse::<JsonPath>().is_err());
+        assert!(".a[".parse::<JsonPath>().is_err());
+        assert!(".a[x]".parse::<JsonPath>().is_err());
+        assert!(".a[0]b".parse::<JsonPath>().is_err());
+        assert_eq!(".a[]".parse::<JsonPath>().unwrap().to_string(), ".a[]");
+    }
+
+    #[test]
+    fn select_works() {
+        let value = json!({
+            "response": {"bytes": 10},
+            "items": [{"price": 1.5}, {"price": 2}, {"name": "x"}],
+        });
+        assert_eq!(select(".", &value), std::slice::from_ref(&value));
+        assert_eq!(select(".response.bytes", &value), [json!(10)]);
+        assert_eq!(select(".items[1].price", &value), [json!(2)]);
+        assert_eq!(select(".items[].price", &value), [json!(1.5), json!(2)]);
+        assert_eq!(select(".response[]", &value), [json!(10)]);
+        assert_eq!(select(".missing.bytes", &value), Vec::<Value>::new());
+        assert_eq!(select(".response.bytes[]", &value), Vec::<Value>::new());
+    }
+}
@@ -1,27 +1,140 @@
+mod accounting;
+mod agg;
+mod decimal;
+mod diagnostics;
+mod follow;
+mod input;
+mod json;
+mod locale;
+mod quantile;
+mod units;
+
+pub use agg::{Agg, Moments};
+pub use decimal::Decimal;
+pub use diagnostics::{Diagnostics, Problem, Tally};
+pub use follow::Follower;
+pub use input::{InputFormat, Record, Records};
+pub use json::JsonPath;
+pub use locale::NumberFormat;
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+pub use quantile::{QuantileMode, Quantiles};
+use regex::Regex;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fmt;
-use std::ops::{Add, AddAssign};
+use std::io::{self, BufRead};
+use std::ops::{Add, AddAssign, Mul};
+use std::str::FromStr;
+pub use units::{DurationFormat, SizeFormat, Units};
 
 /// This enum represents the sum of a sequence of numbers that may be integers or floating point.
-/// Integer is the default. When a floating point number is added to the sum, the type is converted
-/// to Float.
-#[derive(Debug, PartialEq, Clone, Copy)]
+/// Integer is the default. When an integer sum no longer fits in an `i128` it's promoted to
+/// BigInt, so integer sums stay exact no matter how large they get. Adding an exact Decimal
+/// converts an integer sum to a Decimal. When a floating point number is added to the sum, the
+/// type is converted to Float.
+#[derive(Debug, PartialEq, Clone)]
 pub enum Sum {
     Integer(i128),
+    BigInt(BigInt),
+    Decimal(Decimal),
     Float(f64),
 }
 
+impl Sum {
+    /// Returns `n` as an Integer if it fits in an `i128`, otherwise as a BigInt.
+    fn from_bigint(n: BigInt) -> Self {
+        match n.to_i128() {
+            Some(n) => Sum::Integer(n),
+            None => Sum::BigInt(n),
+        }
+    }
+
+    /// Returns this sum as a Float, which is lossy for integers that don't fit in an `f64`.
+    fn to_f64(&self) -> f64 {
+        match self {
+            Sum::Integer(n) => *n as f64,
+            Sum::BigInt(n) => n.to_f64().unwrap_or(f64::NAN),
+            Sum::Decimal(n) => n.to_f64(),
+            Sum::Float(n) => *n,
+        }
+    }
+
+    /// Returns this sum as a Decimal, or `None` if it's a Float.
+    fn to_decimal(&self) -> Option<Decimal> {
+        match self {
+            Sum::Integer(n) => Some(Decimal::from(BigInt::from(*n))),
+            Sum::BigInt(n) => Some(Decimal::from(n.clone())),
+            Sum::Decimal(n) => Some(n.clone()),
+            Sum::Float(_) => None,
+        }
+    }
+
+    /// Compares two Sums by value, regardless of their types, unlike `==` which also compares
+    /// their types. Comparisons with a Float are done as Floats, and are `None` with NaN; all
+    /// others are exact.
+    pub fn cmp_value(&self, other: &Sum) -> Option<Ordering> {
+        match (self, other) {
+            (Sum::Integer(a), Sum::Integer(b)) => a.partial_cmp(b),
+            (a, b) => match (a.to_decimal(), b.to_decimal()) {
+                (Some(a), Some(b)) => a.partial_cmp(&b),
+                _ => a.to_f64().partial_cmp(&b.to_f64()),
+            },
+        }
+    }
+}
+
 impl Add for Sum {
     type Output = Self;
 
-    /// Adds two Sums. If either is a Float, the result will be a Float.
+    /// Adds two Sums. If either is a Float, the result will be a Float. Otherwise, if either is a
+    /// Decimal, the result will be a Decimal. Integer overflow promotes the result to a BigInt.
     fn add(self, other: Self) -> Self {
         match (self, other) {
-            (Sum::Integer(a), Sum::Integer(b)) => {
-                Sum::Integer(a.checked_add(b).expect("integer overflow"))
+            (Sum::Integer(a), Sum::Integer(b)) => match a.checked_add(b) {
+                Some(n) => Sum::Integer(n),
+                None => Sum::BigInt(BigInt::from(a) + b),
+            },
+            (Sum::BigInt(a), Sum::BigInt(b)) => Sum::from_bigint(a + b),
+            (Sum::BigInt(a), Sum::Integer(b)) | (Sum::Integer(b), Sum::BigInt(a)) => {
+                Sum::from_bigint(a + b)
+            }
+            (Sum::Decimal(a), Sum::Decimal(b)) => Sum::Decimal(a + b),
+            (Sum::Decimal(a), Sum::Integer(b)) | (Sum::Integer(b), Sum::Decimal(a)) => {
+                Sum::Decimal(a + Decimal::from(BigInt::from(b)))
             }
-            (Sum::Float(a), Sum::Float(b)) => Sum::Float(a + b),
-            (Sum::Integer(a), Sum::Float(b)) => Sum::Float(a as f64 + b),
-            (Sum::Float(a), Sum::Integer(b)) => Sum::Float(a + b as f64),
+            (Sum::Decimal(a), Sum::BigInt(b)) | (Sum::BigInt(b), Sum::Decimal(a)) => {
+                Sum::Decimal(a + Decimal::from(b))
+            }
+            (a, b) => Sum::Float(a.to_f64() + b.to_f64()),
+        }
+    }
+}
+
+impl Mul for Sum {
+    type Output = Self;
+
+    /// Multiplies two Sums, with the same result types as adding them.
+    fn mul(self, other: Self) -> Self {
+        match (self, other) {
+            (Sum::Integer(a), Sum::Integer(b)) => match a.checked_mul(b) {
+                Some(n) => Sum::Integer(n),
+                None => Sum::BigInt(BigInt::from(a) * b),
+            },
+            (Sum::BigInt(a), Sum::BigInt(b)) => Sum::from_bigint(a * b),
+            (Sum::BigInt(a), Sum::Integer(b)) | (Sum::Integer(b), Sum::BigInt(a)) => {
+                Sum::from_bigint(a * b)
+            }
+            (Sum::Decimal(a), Sum::Decimal(b)) => Sum::Decimal(a * b),
+            (Sum::Decimal(a), Sum::Integer(b)) | (Sum::Integer(b), Sum::Decimal(a)) => {
+                Sum::Decimal(a * Decimal::from(BigInt::from(b)))
+            }
+            (Sum::Decimal(a), Sum::BigInt(b)) | (Sum::BigInt(b), Sum::Decimal(a)) => {
+                Sum::Decimal(a * Decimal::from(b))
+            }
+            (a, b) => Sum::Float(a.to_f64() * b.to_f64()),
         }
     }
 }
@@ -29,7 +142,49 @@ impl Add for Sum {
 impl AddAssign for Sum {
     /// Adds two Sums. If either is a Float, the result will be a Float.
     fn add_assign(&mut self, other: Self) {
-        *self = *self + other;
+        *self = std::mem::take(self) + other;
+    }
+}
+
+impl Default for Sum {
+    fn default() -> Self {
+        Sum::Integer(0)
+    }
+}
+
+/// A [`Sum`] that uses Neumaier's compensated summation for Float values, so rounding error
+/// doesn't build up when adding many floats. Other values are added exactly as with `Sum`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CompensatedSum {
+    sum: Sum,
+    compensation: f64,
+}
+
+impl CompensatedSum {
+    /// Returns the compensated total.
+    pub fn sum(&self) -> Sum {
+        match self.sum {
+            Sum::Float(n) => Sum::Float(n + self.compensation),
+            ref sum => sum.clone(),
+        }
+    }
+}
+
+impl AddAssign<Sum> for CompensatedSum {
+    fn add_assign(&mut self, other: Sum) {
+        let sum = std::mem::take(&mut self.sum);
+        if !matches!((&sum, &other), (Sum::Float(_), _) | (_, Sum::Float(_))) {
+            self.sum = sum + other;
+            return;
+        }
+        let (a, b) = (sum.to_f64(), other.to_f64());
+        let t = a + b;
+        self.compensation += if a.abs() >= b.abs() {
+            (a - t) + b
+        } else {
+            (b - t) + a
+        };
+        self.sum = Sum::Float(t);
     }
 }
 
@@ -37,6 +192,8 @@ impl fmt::Display for Sum {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Sum::Integer(n) => write!(f, "{n}"),
+            Sum::BigInt(n) => write!(f, "{n}"),
+            Sum::Decimal(n) => write!(f, "{n}"),
             Sum::Float(n) => write!(f, "{n}"),
         }
     }
@@ -45,12 +202,957 @@ impl fmt::Display for Sum {
 impl fmt::UpperHex for Sum {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Sum::Integer(n) => fmt::UpperHex::fmt(n, f),
+            Sum::Integer(n) => fmt_signed(f, *n, "0x", format!("{:X}", n.unsigned_abs())),
+            Sum::BigInt(n) => fmt::UpperHex::fmt(n, f),
+            Sum::Decimal(n) => fmt::Display::fmt(n, f),
+            Sum::Float(n) => fmt::Display::fmt(n, f),
+        }
+    }
+}
+
+impl fmt::LowerHex for Sum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sum::Integer(n) => fmt_signed(f, *n, "0x", format!("{:x}", n.unsigned_abs())),
+            Sum::BigInt(n) => fmt::LowerHex::fmt(n, f),
+            Sum::Decimal(n) => fmt::Display::fmt(n, f),
             Sum::Float(n) => fmt::Display::fmt(n, f),
         }
     }
 }
 
+impl fmt::Octal for Sum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sum::Integer(n) => fmt_signed(f, *n, "0o", format!("{:o}", n.unsigned_abs())),
+            Sum::BigInt(n) => fmt::Octal::fmt(n, f),
+            Sum::Decimal(n) => fmt::Display::fmt(n, f),
+            Sum::Float(n) => fmt::Display::fmt(n, f),
+        }
+    }
+}
+
+impl fmt::Binary for Sum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sum::Integer(n) => fmt_signed(f, *n, "0b", format!("{:b}", n.unsigned_abs())),
+            Sum::BigInt(n) => fmt::Binary::fmt(n, f),
+            Sum::Decimal(n) => fmt::Display::fmt(n, f),
+            Sum::Float(n) => fmt::Display::fmt(n, f),
+        }
+    }
+}
+
+/// Formats `n` as a sign and `digits` of its magnitude, like [`BigInt`] does, rather than in
+/// two's complement.
+fn fmt_signed(f: &mut fmt::Formatter<'_>, n: i128, prefix: &str, digits: String) -> fmt::Result {
+    f.pad_integral(n >= 0, prefix, &digits)
+}
+
+/// How to interpret numeric input.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Radix {
+    /// Decimal unless the value has a leading 0x, 0o or 0b prefix (default).
+    Auto,
+    /// Always hex; values with a 0x prefix have it stripped first.
+    Hex,
+    /// Always decimal; prefixed values fail to parse.
+    Decimal,
+    /// Always octal; values with a 0o prefix have it stripped first.
+    Octal,
+    /// Always binary; values with a 0b prefix have it stripped first.
+    Binary,
+}
+
+impl Radix {
+    /// The radices that have a prefix, in the order they're detected by `Radix::Auto`.
+    const PREFIXED: [Radix; 3] = [Radix::Hex, Radix::Octal, Radix::Binary];
+
+    /// The base of this radix, where `Radix::Auto` is 10.
+    pub fn base(self) -> u32 {
+        match self {
+            Radix::Auto | Radix::Decimal => 10,
+            Radix::Hex => 16,
+            Radix::Octal => 8,
+            Radix::Binary => 2,
+        }
+    }
+
+    /// The prefix that marks a value in this radix, like `0x` for hex.
+    pub fn prefix(self) -> Option<&'static str> {
+        match self {
+            Radix::Auto | Radix::Decimal => None,
+            Radix::Hex => Some("0x"),
+            Radix::Octal => Some("0o"),
+            Radix::Binary => Some("0b"),
+        }
+    }
+
+    /// Strips this radix's prefix from `s`, or for `Radix::Auto`, detects the radix from the
+    /// prefix. Returns the rest of `s` and the radix to parse it in.
+    pub fn detect(self, s: &str) -> (&str, Radix) {
+        let candidates: &[Radix] = match self {
+            Radix::Auto => &Radix::PREFIXED,
+            Radix::Decimal => &[],
+            radix => &[radix][..],
+        };
+        candidates
+            .iter()
+            .find_map(|&radix| Some((s.strip_prefix(radix.prefix()?)?, radix)))
+            .unwrap_or(match self {
+                Radix::Auto => (s, Radix::Decimal),
+                radix => (s, radix),
+            })
+    }
+}
+
+impl FromStr for Radix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Radix::Auto),
+            "hex" => Ok(Radix::Hex),
+            "decimal" => Ok(Radix::Decimal),
+            "octal" => Ok(Radix::Octal),
+            "binary" => Ok(Radix::Binary),
+            _ => Err(format!(
+                "unknown radix {s:?} (expected one of auto, hex, decimal, octal, binary)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Radix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Radix::Auto => write!(f, "auto"),
+            Radix::Hex => write!(f, "hex"),
+            Radix::Decimal => write!(f, "decimal"),
+            Radix::Octal => write!(f, "octal"),
+            Radix::Binary => write!(f, "binary"),
+        }
+    }
+}
+
+/// Parses `s` according to the given `radix`. With `Radix::Hex`, `Radix::Octal`
+/// and `Radix::Binary`, only integers are accepted (no float fallback) -- this
+/// keeps them strict so users can trust that a successful parse means the value
+/// was treated as hex, octal or binary. `Radix::Auto` is treated the same as `Radix::Decimal`
+/// here; the caller is responsible for resolving prefix detection before calling.
+pub fn parse_value(s: &str, radix: Radix) -> Result<Sum, &'static str> {
+    let radix_num = radix.base();
+    if let Ok(n) = i128::from_str_radix(s, radix_num) {
+        return Ok(Sum::Integer(n));
+    }
+    // Too large for an i128, but still an integer. Only digits are checked first, so that other
+    // values don't pay for a BigInt parse, and because BigInt allows `_` separators.
+    let digits = s.strip_prefix(['+', '-']).unwrap_or(s);
+    if !digits.is_empty() && digits.chars().all(|c| c.is_digit(radix_num)) {
+        if let Some(n) = BigInt::parse_bytes(s.as_bytes(), radix_num) {
+            return Ok(Sum::BigInt(n));
+        }
+    }
+    match radix {
+        Radix::Hex => return Err("Failed to parse as hex, treating as 0"),
+        Radix::Octal => return Err("Failed to parse as octal, treating as 0"),
+        Radix::Binary => return Err("Failed to parse as binary, treating as 0"),
+        Radix::Auto | Radix::Decimal => {}
+    }
+    if let Ok(n) = s.parse::<f64>() {
+        return Ok(Sum::Float(n));
+    }
+    Err("Failed to parse (use --radix=hex if hex), treating as 0")
+}
+
+/// A raw value after it's been cleaned up and parsed, but before it's added to any sums.
+struct Parsed<'a> {
+    clean_str: Cow<'a, str>,
+    result: Result<Sum, &'static str>,
+    radix: Radix,
+    notes: Vec<&'static str>,
+}
+
+/// Selects a value from each line of input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field {
+    /// A 1-based field index, where 0 is the full line. For JSON input, indexes an array, and 0
+    /// is the whole value.
+    Index(usize),
+    /// A path into JSON input, which may select more than one value.
+    Path(JsonPath),
+}
+
+impl Field {
+    /// Whether selecting this field requires splitting lines of text.
+    fn is_split(&self) -> bool {
+        matches!(self, Field::Index(f) if *f > 0)
+    }
+
+    /// Returns the values in `value` that this field selects.
+    fn select_json<'a>(&self, value: &'a Value) -> Vec<&'a Value> {
+        match self {
+            Field::Index(0) => vec![value],
+            Field::Index(f) => value.get(f - 1).into_iter().collect(),
+            Field::Path(path) => path.select(value),
+        }
+    }
+}
+
+impl From<usize> for Field {
+    fn from(field: usize) -> Self {
+        Field::Index(field)
+    }
+}
+
+impl From<JsonPath> for Field {
+    fn from(path: JsonPath) -> Self {
+        Field::Path(path)
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Index(field) => write!(f, "{field}"),
+            Field::Path(path) => write!(f, "{path}"),
+        }
+    }
+}
+
+/// A single value that was extracted from a line of input and added to the sum.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// The field the value was taken from.
+    pub field: Field,
+    /// The key of the group the value was added to, if grouping.
+    pub group: Option<String>,
+    /// The parsed numeric value, or 0 if parsing failed.
+    pub n: Sum,
+    /// The running sum for `field` up to and including `n`.
+    pub sum: Sum,
+    /// The effective radix used when parsing the value (`Hex`, `Decimal`, `Octal` or `Binary`).
+    pub radix: Radix,
+    /// The raw string data that was parsed.
+    pub raw_str: String,
+    /// If present, the warning message from a failed parse.
+    pub err: Option<&'static str>,
+    /// Notes about notation that was stripped from the value before parsing it, like currency
+    /// symbols or accounting-style negatives.
+    pub notes: Vec<&'static str>,
+}
+
+/// The result of feeding input to a [`Summer`], for a single field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    /// The total of all parsed values.
+    pub sum: Sum,
+    /// The number of lines read, including skipped ones.
+    pub lines: usize,
+    /// The number of values added to the sum, including failed parses counted as 0.
+    pub values: usize,
+    /// The number of values that failed to parse.
+    pub errors: usize,
+    /// The smallest successfully parsed value, if stats are being tracked.
+    pub min: Option<Sum>,
+    /// The largest successfully parsed value, if stats are being tracked.
+    pub max: Option<Sum>,
+    /// The mean and variance of the successfully parsed values, if stats are being tracked.
+    pub moments: Moments,
+    /// The successfully parsed values, if percentiles are being tracked.
+    pub quantiles: Option<Quantiles>,
+}
+
+impl Summary {
+    /// The number of successfully parsed values.
+    pub fn count(&self) -> usize {
+        self.values - self.errors
+    }
+}
+
+impl Default for Summary {
+    fn default() -> Self {
+        Summary {
+            sum: Sum::Integer(0),
+            lines: 0,
+            values: 0,
+            errors: 0,
+            min: None,
+            max: None,
+            moments: Moments::default(),
+            quantiles: None,
+        }
+    }
+}
+
+/// Accumulates the values of a single field into a [`Summary`].
+#[derive(Debug, Clone)]
+struct Accumulator {
+    compensated: Option<CompensatedSum>,
+    /// Whether to track the min, max and moments.
+    stats: bool,
+    summary: Summary,
+}
+
+impl Accumulator {
+    /// Adds `n` to the summary. Values that failed to parse are counted as errors, and only
+    /// contribute their value (0) to the sum.
+    fn add(&mut self, n: &Sum, parsed: bool) {
+        let summary = &mut self.summary;
+        if parsed {
+            if self.stats {
+                // NaN isn't smaller or larger than anything, so it would stick as the min and max.
+                if !matches!(n, Sum::Float(n) if n.is_nan()) {
+                    let beats = |other: &Option<Sum>, ordering| {
+                        let other = other.as_ref();
+                        other.is_none_or(|other| n.cmp_value(other) == Some(ordering))
+                    };
+                    if beats(&summary.min, Ordering::Less) {
+                        summary.min = Some(n.clone());
+                    }
+                    if beats(&summary.max, Ordering::Greater) {
+                        summary.max = Some(n.clone());
+                    }
+                }
+                summary.moments.add(n.to_f64());
+            }
+            if let Some(quantiles) = &mut summary.quantiles {
+                quantiles.add(n);
+            }
+        } else {
+            summary.errors += 1;
+        }
+        match &mut self.compensated {
+            Some(compensated) => {
+                *compensated += n.clone();
+                summary.sum = compensated.sum();
+            }
+            None => summary.sum += n.clone(),
+        }
+        summary.values += 1;
+    }
+}
+
+/// The lines that share a value in the group-by field, see [`SummerBuilder::group_by`], or that
+/// came from the same input, see [`Summer::start_input`].
+#[derive(Debug, Clone)]
+pub struct Group {
+    key: String,
+    accumulators: Vec<Accumulator>,
+}
+
+impl Group {
+    /// The value of the group-by field that the lines in this group share, or the input's name.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the result for this group, for each field in the order they were given.
+    pub fn summaries(&self) -> impl Iterator<Item = &Summary> {
+        self.accumulators.iter().map(|acc| &acc.summary)
+    }
+}
+
+/// Builds a [`Summer`]. See [`Summer::builder`].
+#[derive(Debug, Clone)]
+pub struct SummerBuilder {
+    fields: Vec<Field>,
+    group_by: Option<Field>,
+    format: InputFormat,
+    delimiter: Regex,
+    extract: Option<Regex>,
+    all_numbers: bool,
+    units: Option<Units>,
+    number_format: Option<NumberFormat>,
+    radix: Radix,
+    exact: bool,
+    precise_float: bool,
+    stats: bool,
+    quantiles: Option<QuantileMode>,
+}
+
+impl Default for SummerBuilder {
+    fn default() -> Self {
+        SummerBuilder {
+            fields: vec![Field::Index(0)],
+            group_by: None,
+            format: InputFormat::Text,
+            delimiter: Regex::new(r"\s+").expect("valid default delimiter"),
+            extract: None,
+            all_numbers: false,
+            units: None,
+            number_format: None,
+            radix: Radix::Auto,
+            exact: false,
+            precise_float: false,
+            stats: false,
+            quantiles: None,
+        }
+    }
+}
+
+impl SummerBuilder {
+    /// The field to sum, like a 1-based index. 0 (the default) uses the full line.
+    pub fn field(self, field: impl Into<Field>) -> Self {
+        self.fields([field])
+    }
+
+    /// The fields to sum, each with its own [`Summary`]. 0 uses the full line, as does an empty
+    /// list.
+    pub fn fields(mut self, fields: impl IntoIterator<Item = impl Into<Field>>) -> Self {
+        self.fields = fields.into_iter().map(Into::into).collect();
+        if self.fields.is_empty() {
+            self.fields.push(Field::Index(0));
+        }
+        self
+    }
+
+    /// The field whose value groups lines together, so that each distinct value gets its own
+    /// [`Summary`] for each field, see [`Summer::groups`]. Defaults to `None`, which doesn't
+    /// group lines.
+    pub fn group_by(mut self, field: Option<Field>) -> Self {
+        self.group_by = field;
+        self
+    }
+
+    /// The format that [`Summer::feed_reader`] reads. Defaults to `InputFormat::Text`.
+    pub fn format(mut self, format: InputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// The regex on which to split lines of text into fields. Defaults to `\s+`.
+    pub fn delimiter(mut self, delimiter: Regex) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// A regex whose capture group is the value to sum from each line, instead of a field. Uses
+    /// the first named group if there is one, otherwise the first group, otherwise the whole
+    /// match. Lines that don't match are skipped. Defaults to `None`.
+    pub fn extract(mut self, extract: Option<Regex>) -> Self {
+        self.extract = extract;
+        self
+    }
+
+    /// Sum every number found in each selected field, like `3` and `4` in `3 apples and 4
+    /// pears`, instead of parsing the whole field as one number. Numbers are decimal integers
+    /// and floats written in the [`SummerBuilder::number_format`] (`1,234.5` by default), or
+    /// `0x` hex, `0o` octal and `0b` binary integers. Defaults to false.
+    pub fn all_numbers(mut self, all_numbers: bool) -> Self {
+        self.all_numbers = all_numbers;
+        self
+    }
+
+    /// The units of the input, like sizes such as `1.5G` or durations such as `1h30m`. Values are
+    /// converted to the base unit, like bytes or seconds, before they're summed. Defaults to
+    /// `None`, which only accepts plain numbers.
+    pub fn units(mut self, units: Option<Units>) -> Self {
+        self.units = units;
+        self
+    }
+
+    /// The separators that numbers use for the fractional part and groups of digits, like
+    /// `1.234,5` for `de-DE`. Defaults to `None`, which strips commas like `en-US`, but warns
+    /// about it because the input may use another format.
+    pub fn number_format(mut self, number_format: Option<NumberFormat>) -> Self {
+        self.number_format = number_format;
+        self
+    }
+
+    /// How to interpret numeric input. Defaults to `Radix::Auto`.
+    pub fn radix(mut self, radix: Radix) -> Self {
+        self.radix = radix;
+        self
+    }
+
+    /// Sum decimal values like `1.10` exactly as a [`Sum::Decimal`] instead of as a Float.
+    /// Defaults to false.
+    pub fn exact(mut self, exact: bool) -> Self {
+        self.exact = exact;
+        self
+    }
+
+    /// Sum Float values with compensated summation (see [`CompensatedSum`]), which is more
+    /// accurate but slower. Defaults to false.
+    pub fn precise_float(mut self, precise_float: bool) -> Self {
+        self.precise_float = precise_float;
+        self
+    }
+
+    /// Track the min, max and moments of the parsed values, see [`Summary::min`],
+    /// [`Summary::max`] and [`Summary::moments`]. Defaults to false, which doesn't track them.
+    pub fn stats(mut self, stats: bool) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    /// Track the parsed values so that percentiles can be computed, see [`Summary::quantiles`].
+    /// Defaults to `None`, which doesn't track them.
+    pub fn quantiles(mut self, mode: Option<QuantileMode>) -> Self {
+        self.quantiles = mode;
+        self
+    }
+
+    /// Builds the [`Summer`] with the options set so far.
+    pub fn build(self) -> Summer {
+        let accumulator = Accumulator {
+            compensated: self.precise_float.then(CompensatedSum::default),
+            stats: self.stats,
+            summary: Summary {
+                quantiles: self.quantiles.map(Quantiles::new),
+                ..Summary::default()
+            },
+        };
+        let warn_stripped = self.number_format.is_none();
+        let number_format = self.number_format.unwrap_or_default();
+        Summer {
+            accumulators: vec![accumulator.clone(); self.fields.len()],
+            empty: accumulator,
+            groups: Vec::new(),
+            group_index: HashMap::new(),
+            inputs: Vec::new(),
+            diagnostics: Diagnostics::default(),
+            fields: self.fields,
+            group_by: self.group_by,
+            format: self.format,
+            delimiter: self.delimiter,
+            extract: self.extract.map(|re| {
+                let group = re
+                    .capture_names()
+                    .position(|name| name.is_some())
+                    .unwrap_or(if re.captures_len() > 1 { 1 } else { 0 });
+                (re, group)
+            }),
+            numbers: self.all_numbers.then(|| {
+                let format = &number_format;
+                // A number must not follow a word character or a decimal separator, so that `v2`
+                // and `1.2.3` don't yield extra numbers. The regex crate has no lookbehind, hence
+                // the group.
+                let decimal = regex::escape(&format.decimal().to_string());
+                Regex::new(&format!(
+                    r"(?:^|[^\w{decimal}])(0[xX][0-9a-fA-F]+|0o[0-7]+|0b[01]+|{})",
+                    format.number_pattern()
+                ))
+                .expect("valid number regex")
+            }),
+            units: self.units,
+            number_format,
+            warn_stripped,
+            radix: self.radix,
+            exact: self.exact,
+        }
+    }
+}
+
+/// Sums numbers from lines of text input, using the same field splitting, comma stripping and
+/// radix detection as the `sumcol` command-line tool.
+///
+/// ```
+/// use sumcol::{Sum, Summer};
+///
+/// let mut summer = Summer::builder().field(2).build();
+/// summer.feed_line("a 1 x");
+/// summer.feed_line("b 0x10 y");
+/// assert_eq!(summer.summary().sum, Sum::Integer(17));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Summer {
+    fields: Vec<Field>,
+    group_by: Option<Field>,
+    format: InputFormat,
+    delimiter: Regex,
+    /// The regex to extract values with, and the index of its capture group to use.
+    extract: Option<(Regex, usize)>,
+    /// The regex that finds every number in a field, if summing all of them.
+    numbers: Option<Regex>,
+    units: Option<Units>,
+    number_format: NumberFormat,
+    /// Whether to warn when separators are stripped, because the number format is the default
+    /// rather than one that was asked for.
+    warn_stripped: bool,
+    radix: Radix,
+    exact: bool,
+    accumulators: Vec<Accumulator>,
+    /// An empty accumulator, for starting new groups.
+    empty: Accumulator,
+    groups: Vec<Group>,
+    group_index: HashMap<String, usize>,
+    /// A group for each input, if they're being summed separately.
+    inputs: Vec<Group>,
+    diagnostics: Diagnostics,
+}
+
+impl Summer {
+    /// Returns a [`SummerBuilder`] with the defaults, which sums the whole line as one field.
+    pub fn builder() -> SummerBuilder {
+        SummerBuilder::default()
+    }
+
+    /// Adds the selected values from `line` to their sums, returning an [`Entry`] for each one.
+    /// Blank lines, and fields the line doesn't have, are skipped.
+    pub fn feed_line(&mut self, line: &str) -> Vec<Entry> {
+        let line = line.trim();
+        tracing::debug!(?line, "Read line");
+        let split: Vec<&str> = if self
+            .fields
+            .iter()
+            .chain(&self.group_by)
+            .any(Field::is_split)
+        {
+            self.delimiter.split(line).collect()
+        } else {
+            Vec::new()
+        };
+        self.feed_fields(line, &split)
+    }
+
+    /// Like [`Summer::feed_line`], but for a record that's already split into fields, such as a
+    /// row of CSV. Field 0 is all of the fields joined by spaces.
+    pub fn feed_record<S: AsRef<str>>(&mut self, record: &[S]) -> Vec<Entry> {
+        let split: Vec<&str> = record.iter().map(|field| field.as_ref().trim()).collect();
+        tracing::debug!(?split, "Read record");
+        let line = split.join(" ");
+        self.feed_fields(line.trim(), &split)
+    }
+
+    /// Like [`Summer::feed_line`], but for a line of JSON. Fields select values from it, and
+    /// each number, or string that parses as one, is added to the sum. Values that aren't
+    /// numeric, and fields that select nothing, are skipped.
+    pub fn feed_json(&mut self, line: &str) -> Vec<Entry> {
+        self.count_line();
+        let line = line.trim();
+        tracing::debug!(?line, "Read JSON");
+        if line.is_empty() {
+            return Vec::new();
+        }
+        let value: Value = match serde_json::from_str(line) {
+            Ok(value) => value,
+            Err(err) => {
+                tracing::warn!(line, %err, "Invalid JSON, skipping");
+                self.diagnostics.record(Problem::Skipped, line);
+                return Vec::new();
+            }
+        };
+        let group = match &self.group_by {
+            Some(field) => {
+                let key = match field.select_json(&value).first() {
+                    Some(Value::String(key)) => key.clone(),
+                    Some(key) => key.to_string(),
+                    None => {
+                        tracing::warn!(%field, line, "Group-by field not found, skipping");
+                        self.diagnostics.record(Problem::Skipped, line);
+                        return Vec::new();
+                    }
+                };
+                Some(self.group(&key))
+            }
+            None => None,
+        };
+        let mut raw_strs = Vec::new();
+        for (index, field) in self.fields.iter().enumerate() {
+            let values = field.select_json(&value);
+            if values.is_empty() {
+                tracing::warn!(%field, line, "Field not found, skipping");
+                self.diagnostics.record(Problem::Skipped, line);
+            }
+            for value in values {
+                match value {
+                    Value::Number(n) => raw_strs.push((index, n.to_string(), false)),
+                    Value::String(s) => raw_strs.push((index, s.clone(), true)),
+                    value => {
+                        tracing::warn!(%field, %value, "Value is not a number, skipping");
+                        self.diagnostics
+                            .record(Problem::Skipped, &value.to_string());
+                    }
+                }
+            }
+        }
+        let mut entries = Vec::with_capacity(raw_strs.len());
+        for (index, raw_str, is_string) in raw_strs {
+            let parsed = self.parse_raw(&raw_str);
+            // Strings that aren't numbers are skipped like any other value that isn't a number.
+            if is_string && parsed.result.is_err() {
+                let field = &self.fields[index];
+                tracing::warn!(%field, value = ?raw_str, "Value is not a number, skipping");
+                self.diagnostics.record(Problem::Skipped, &raw_str);
+                continue;
+            }
+            entries.push(self.add_parsed(index, group, &raw_str, parsed));
+        }
+        entries
+    }
+
+    /// Feeds a [`Record`] to [`Summer::feed_line`], [`Summer::feed_record`] or
+    /// [`Summer::feed_json`].
+    pub fn feed(&mut self, record: &Record) -> Vec<Entry> {
+        match record {
+            Record::Line(line) => self.feed_line(line),
+            Record::Fields(fields) => self.feed_record(fields),
+            Record::Json(line) => self.feed_json(line),
+        }
+    }
+
+    /// Adds the selected values from `split` to their sums, where `line` is the whole input.
+    fn feed_fields(&mut self, line: &str, split: &[&str]) -> Vec<Entry> {
+        self.count_line();
+        if line.is_empty() {
+            return Vec::new();
+        }
+        let extracted = match &self.extract {
+            Some((re, group)) => {
+                let Some(value) = re.captures(line).and_then(|caps| caps.get(*group)) else {
+                    tracing::debug!(line, "No match for --extract, skipping");
+                    return Vec::new();
+                };
+                Some(value.as_str())
+            }
+            None => None,
+        };
+        let select = |field: &Field| match field {
+            Field::Index(0) => Some(line),
+            Field::Index(f) => split.get(f - 1).copied(),
+            Field::Path(_) => None,
+        };
+        let group = match &self.group_by {
+            Some(field) => {
+                let Some(key) = select(field) else {
+                    tracing::warn!(%field, line, "Group-by field out of range, skipping");
+                    self.diagnostics.record(Problem::Skipped, line);
+                    return Vec::new();
+                };
+                Some(self.group(key.trim()))
+            }
+            None => None,
+        };
+        let mut entries = Vec::with_capacity(self.fields.len());
+        for index in 0..self.fields.len() {
+            let Some(raw_str) = extracted.or_else(|| select(&self.fields[index])) else {
+                tracing::warn!(
+                    field = %self.fields[index],
+                    line,
+                    "Field index out of range, skipping"
+                );
+                self.diagnostics.record(Problem::Skipped, line);
+                continue;
+            };
+            let raw_strs: Vec<&str> = match &self.numbers {
+                Some(numbers) => numbers
+                    .captures_iter(raw_str)
+                    .filter_map(|caps| caps.get(1))
+                    .map(|m| m.as_str())
+                    .collect(),
+                None => vec![raw_str],
+            };
+            for raw_str in raw_strs {
+                entries.push(self.feed_value(index, group, raw_str));
+            }
+        }
+        entries
+    }
+
+    /// Counts a line in the totals, and in the current input if there is one.
+    fn count_line(&mut self) {
+        let input = self.inputs.last_mut().map(|input| &mut input.accumulators);
+        for acc in self
+            .accumulators
+            .iter_mut()
+            .chain(input.into_iter().flatten())
+        {
+            acc.summary.lines += 1;
+        }
+    }
+
+    /// Returns the index of the group for `key`, creating it if needed, and counts a line in it.
+    fn group(&mut self, key: &str) -> usize {
+        let index = match self.group_index.get(key) {
+            Some(&index) => index,
+            None => {
+                self.groups.push(Group {
+                    key: key.to_string(),
+                    accumulators: vec![self.empty.clone(); self.fields.len()],
+                });
+                self.group_index
+                    .insert(key.to_string(), self.groups.len() - 1);
+                self.groups.len() - 1
+            }
+        };
+        for acc in &mut self.groups[index].accumulators {
+            acc.summary.lines += 1;
+        }
+        index
+    }
+
+    /// Parses `raw_str` and adds it to the sum of the `index`th field, and to the given group.
+    fn feed_value(&mut self, index: usize, group: Option<usize>, raw_str: &str) -> Entry {
+        let parsed = self.parse_raw(raw_str);
+        self.add_parsed(index, group, raw_str, parsed)
+    }
+
+    /// Cleans up `raw_str` and parses it, without adding it to any sums.
+    fn parse_raw<'a>(&mut self, raw_str: &'a str) -> Parsed<'a> {
+        let (trimmed, notes) = accounting::strip(raw_str.trim());
+        if !notes.is_empty() {
+            tracing::debug!(
+                original = raw_str,
+                clean = %trimmed,
+                ?notes,
+                "Stripped accounting notation"
+            );
+        }
+        let clean_str = match self.number_format.normalize(&trimmed) {
+            Cow::Borrowed(_) => trimmed,
+            Cow::Owned(clean_str) => {
+                if self.warn_stripped {
+                    tracing::warn!(
+                        original = %trimmed,
+                        clean = clean_str.as_str(),
+                        "Stripped commas from value"
+                    );
+                    self.diagnostics.record(Problem::StrippedCommas, &trimmed);
+                }
+                Cow::Owned(clean_str)
+            }
+        };
+        let (parsed, radix) = match self.units.and_then(|units| units.split(&clean_str)) {
+            Some(parts) => {
+                let mut sum = Ok(Sum::Integer(0));
+                let mut radix = Radix::Decimal;
+                for (number, factor) in parts {
+                    let (n, r) = self.parse(number);
+                    radix = r;
+                    sum = sum.and_then(|sum| Ok(sum + n? * factor));
+                }
+                (sum, radix)
+            }
+            None => self.parse(&clean_str),
+        };
+        Parsed {
+            clean_str,
+            result: parsed,
+            radix,
+            notes,
+        }
+    }
+
+    /// Adds a value parsed from `raw_str` to the sum of the `index`th field, and to the given
+    /// group. Values that failed to parse are counted as 0.
+    fn add_parsed(
+        &mut self,
+        index: usize,
+        group: Option<usize>,
+        raw_str: &str,
+        parsed: Parsed,
+    ) -> Entry {
+        let Parsed {
+            clean_str,
+            result,
+            radix,
+            notes,
+        } = parsed;
+        let (n, err) = match result {
+            Ok(n) => (n, None),
+            Err(msg) => {
+                tracing::warn!(?clean_str, "{msg}");
+                self.diagnostics.record(Problem::ParseFailure, raw_str);
+                (Sum::Integer(0), Some(msg))
+            }
+        };
+        let acc = &mut self.accumulators[index];
+        acc.add(&n, err.is_none());
+        let group = group.map(|group| {
+            let group = &mut self.groups[group];
+            group.accumulators[index].add(&n, err.is_none());
+            group.key.clone()
+        });
+        if let Some(input) = self.inputs.last_mut() {
+            input.accumulators[index].add(&n, err.is_none());
+        }
+        Entry {
+            field: self.fields[index].clone(),
+            group,
+            n,
+            sum: acc.summary.sum.clone(),
+            radix,
+            raw_str: raw_str.to_string(),
+            err,
+            notes,
+        }
+    }
+
+    /// Parses a single number, returning the effective radix that was used.
+    fn parse(&mut self, s: &str) -> (Result<Sum, &'static str>, Radix) {
+        let (s, radix) = self.radix.detect(s);
+        // Durations are always exact, so that 0.1s + 0.2s is 0.3s.
+        let exact = self.exact || self.units == Some(Units::Duration);
+        let decimal = (exact && radix == Radix::Decimal).then(|| Decimal::parse(s));
+        let parsed = match decimal.flatten() {
+            Some(n) => Ok(Sum::Decimal(n)),
+            None => parse_value(s, radix),
+        };
+        if exact && matches!(parsed, Ok(Sum::Float(_))) {
+            tracing::warn!(s, "Not an exact decimal, summing as a float");
+            self.diagnostics.record(Problem::FloatFallback, s);
+        }
+        (parsed, radix)
+    }
+
+    /// Feeds every record of `reader`, in the builder's [`InputFormat`], to [`Summer::feed`].
+    pub fn feed_reader<R: BufRead>(&mut self, reader: R) -> io::Result<()> {
+        for record in Records::new(reader, self.format) {
+            self.feed(&record?);
+        }
+        Ok(())
+    }
+
+    /// Returns the result of everything fed so far, for the first field.
+    pub fn summary(&self) -> &Summary {
+        &self.accumulators[0].summary
+    }
+
+    /// Returns the result of everything fed so far, for each field in the order they were given.
+    pub fn summaries(&self) -> impl Iterator<Item = &Summary> {
+        self.accumulators.iter().map(|acc| &acc.summary)
+    }
+
+    /// Prepares every summary for computing aggregations, like sorting the values for exact
+    /// percentiles, which is faster than computing them without it. See [`Quantiles::finish`].
+    pub fn finish(&mut self) {
+        let groups = self.groups.iter_mut().chain(&mut self.inputs);
+        let accumulators = groups.flat_map(|group| &mut group.accumulators);
+        for acc in self.accumulators.iter_mut().chain(accumulators) {
+            if let Some(quantiles) = &mut acc.summary.quantiles {
+                quantiles.finish();
+            }
+        }
+    }
+
+    /// Returns the groups in the order they were first seen. Empty unless grouping.
+    pub fn groups(&self) -> &[Group] {
+        &self.groups
+    }
+
+    /// Starts a new input, like a file, named `name`. Everything fed from now on is also summed
+    /// separately for this input, as well as in the totals. See [`Summer::inputs`].
+    pub fn start_input(&mut self, name: &str) {
+        self.inputs.push(Group {
+            key: name.to_string(),
+            accumulators: vec![self.empty.clone(); self.fields.len()],
+        });
+    }
+
+    /// Returns the inputs in the order they were started, keyed by name. Empty unless
+    /// [`Summer::start_input`] was called.
+    pub fn inputs(&self) -> &[Group] {
+        &self.inputs
+    }
+
+    /// Returns the problems with everything fed so far, which are also logged as warnings.
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,7 +1161,7 @@ mod tests {
     fn sum_integer_works() {
         let a = Sum::Integer(1);
         let b = Sum::Integer(2);
-        assert_eq!(a + b, Sum::Integer(3));
+        assert_eq!(a.clone() + b.clone(), Sum::Integer(3));
 
         let mut c = a;
         c += b;
@@ -77,7 +1179,7 @@ mod tests {
     fn sum_mixed_works() {
         let a = Sum::Integer(1);
         let b = Sum::Float(0.2);
-        assert_eq!(a + b, Sum::Float(1.2));
+        assert_eq!(a.clone() + b.clone(), Sum::Float(1.2));
         assert_eq!(b + a, Sum::Float(1.2));
     }
 
@@ -93,9 +1195,574 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
-    fn sum_integer_overflow_panics() {
+    fn sum_integer_overflow_promotes_to_bigint() {
         let mut a = Sum::Integer(i128::MAX);
         a += Sum::Integer(1);
+        assert_eq!(a, Sum::BigInt(BigInt::from(i128::MAX) + 1));
+
+        a += Sum::Integer(-1);
+        assert_eq!(a, Sum::Integer(i128::MAX));
+    }
+
+    #[test]
+    fn sum_mul_works() {
+        assert_eq!(Sum::Integer(3) * Sum::Integer(-4), Sum::Integer(-12));
+        assert_eq!(
+            Sum::Integer(i128::MAX) * Sum::Integer(2),
+            Sum::BigInt(BigInt::from(i128::MAX) * 2)
+        );
+        assert_eq!(
+            Sum::Decimal(Decimal::new(15, 1)) * Sum::Integer(1024),
+            Sum::Decimal(Decimal::new(15360, 1))
+        );
+        assert_eq!(Sum::Float(1.5) * Sum::Integer(2), Sum::Float(3.0));
+    }
+
+    #[test]
+    fn sum_bigint_mixed_works() {
+        let a = Sum::BigInt(BigInt::from(i128::MAX) * 2);
+        assert_eq!(
+            a.clone() + Sum::Float(0.5),
+            Sum::Float(i128::MAX as f64 * 2.0 + 0.5)
+        );
+        assert_eq!(format!("{a:#X}"), "0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFE");
+    }
+
+    #[test]
+    fn parse_integer_decimal() {
+        assert_eq!(parse_value("42", Radix::Decimal), Ok(Sum::Integer(42)));
+    }
+
+    #[test]
+    fn parse_integer_hex() {
+        assert_eq!(parse_value("FF", Radix::Hex), Ok(Sum::Integer(255)));
+    }
+
+    #[test]
+    fn parse_negative_integer() {
+        assert_eq!(parse_value("-5", Radix::Decimal), Ok(Sum::Integer(-5)));
+    }
+
+    #[test]
+    fn parse_float() {
+        assert_eq!(parse_value("1.5", Radix::Decimal), Ok(Sum::Float(1.5)));
+    }
+
+    #[test]
+    fn parse_negative_float() {
+        assert_eq!(parse_value("-1.5", Radix::Decimal), Ok(Sum::Float(-1.5)));
+    }
+
+    #[test]
+    fn parse_scientific_notation() {
+        assert_eq!(parse_value("3e0", Radix::Decimal), Ok(Sum::Float(3.0)));
+    }
+
+    #[test]
+    fn parse_float_in_hex_mode_fails() {
+        assert_eq!(
+            parse_value("1.5", Radix::Hex),
+            Err("Failed to parse as hex, treating as 0")
+        );
+    }
+
+    #[test]
+    fn parse_invalid_decimal() {
+        assert_eq!(
+            parse_value("OOPS", Radix::Decimal),
+            Err("Failed to parse (use --radix=hex if hex), treating as 0")
+        );
+    }
+
+    #[test]
+    fn parse_empty_string() {
+        assert_eq!(
+            parse_value("", Radix::Decimal),
+            Err("Failed to parse (use --radix=hex if hex), treating as 0")
+        );
+    }
+
+    #[test]
+    fn parse_overflow_promotes_to_bigint() {
+        let s = "999999999999999999999999999999999999999999";
+        assert_eq!(
+            parse_value(s, Radix::Decimal),
+            Ok(Sum::BigInt(s.parse().unwrap()))
+        );
+        let s = "-999999999999999999999999999999999999999999";
+        assert_eq!(
+            parse_value(s, Radix::Decimal),
+            Ok(Sum::BigInt(s.parse().unwrap()))
+        );
+        assert!(parse_value("-", Radix::Decimal).is_err());
+        assert!(matches!(
+            parse_value("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF", Radix::Hex),
+            Ok(Sum::BigInt(_))
+        ));
+    }
+
+    #[test]
+    fn parse_underscore_fails() {
+        assert!(parse_value("1_000", Radix::Decimal).is_err());
+    }
+
+    #[test]
+    fn parse_integer_octal() {
+        assert_eq!(parse_value("755", Radix::Octal), Ok(Sum::Integer(0o755)));
+        assert_eq!(
+            parse_value("8", Radix::Octal),
+            Err("Failed to parse as octal, treating as 0")
+        );
+    }
+
+    #[test]
+    fn parse_integer_binary() {
+        assert_eq!(parse_value("1010", Radix::Binary), Ok(Sum::Integer(10)));
+        assert_eq!(
+            parse_value("0.1", Radix::Binary),
+            Err("Failed to parse as binary, treating as 0")
+        );
+    }
+
+    #[test]
+    fn detect_radix_works() {
+        assert_eq!(Radix::Auto.detect("0x1F"), ("1F", Radix::Hex));
+        assert_eq!(Radix::Auto.detect("0o17"), ("17", Radix::Octal));
+        assert_eq!(Radix::Auto.detect("0b101"), ("101", Radix::Binary));
+        assert_eq!(Radix::Auto.detect("42"), ("42", Radix::Decimal));
+        assert_eq!(Radix::Decimal.detect("0b1"), ("0b1", Radix::Decimal));
+        // 0b1 is a hex number.
+        assert_eq!(Radix::Hex.detect("0b1"), ("0b1", Radix::Hex));
+        assert_eq!(Radix::Binary.detect("0b1"), ("1", Radix::Binary));
+        assert_eq!(Radix::Octal.detect("17"), ("17", Radix::Octal));
+    }
+
+    #[test]
+    fn parse_radix_works() {
+        assert_eq!("hex".parse(), Ok(Radix::Hex));
+        assert!("16".parse::<Radix>().is_err());
+        for radix in [
+            Radix::Auto,
+            Radix::Hex,
+            Radix::Decimal,
+            Radix::Octal,
+            Radix::Binary,
+        ] {
+            assert_eq!(radix.to_string().parse(), Ok(radix));
+        }
+    }
+
+    #[test]
+    fn format_radixes() {
+        assert_eq!(format!("{:#o}", Sum::Integer(0o755)), "0o755");
+        assert_eq!(format!("{:#b}", Sum::Integer(5)), "0b101");
+        assert_eq!(format!("{:#x}", Sum::Integer(255)), "0xff");
+        assert_eq!(format!("{:#b}", Sum::Float(1.5)), "1.5");
+        // Negative integers are signed like big ones, not in two's complement.
+        assert_eq!(format!("{:#x}", Sum::Integer(-255)), "-0xff");
+        assert_eq!(
+            format!("{:#X}", Sum::BigInt(BigInt::from(-255))),
+            format!("{:#X}", Sum::Integer(-255))
+        );
+        assert_eq!(format!("{:#08o}", Sum::Integer(-8)), "-0o00010");
+    }
+
+    #[test]
+    fn parse_invalid_hex() {
+        assert_eq!(
+            parse_value("GG", Radix::Hex),
+            Err("Failed to parse as hex, treating as 0")
+        );
+    }
+
+    #[test]
+    fn summer_field_works() {
+        let mut summer = Summer::builder().field(2).stats(true).build();
+        assert_eq!(summer.feed_line("a 1 x")[0].sum, Sum::Integer(1));
+        assert_eq!(summer.feed_line("b 2.5 y")[0].sum, Sum::Float(3.5));
+        assert_eq!(summer.feed_line("short"), []);
+        assert_eq!(summer.feed_line(""), []);
+        let summary = summer.summary();
+        assert_eq!(summary.sum, Sum::Float(3.5));
+        assert_eq!(summary.lines, 4);
+        assert_eq!(summary.values, 2);
+        assert_eq!(summary.errors, 0);
+        assert_eq!(summary.min, Some(Sum::Integer(1)));
+        assert_eq!(summary.max, Some(Sum::Float(2.5)));
+    }
+
+    #[test]
+    fn summer_multiple_fields_works() {
+        let mut summer = Summer::builder().fields([3, 1, 0]).build();
+        let entries = summer.feed_line("1 x 2");
+        let fields: Vec<_> = entries
+            .iter()
+            .map(|e| (e.field.clone(), e.n.clone()))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                (Field::Index(3), Sum::Integer(2)),
+                (Field::Index(1), Sum::Integer(1)),
+                (Field::Index(0), Sum::Integer(0))
+            ]
+        );
+        assert_eq!(summer.feed_line("3 y").len(), 2);
+        let sums: Vec<_> = summer.summaries().map(|s| s.sum.clone()).collect();
+        assert_eq!(sums, [Sum::Integer(2), Sum::Integer(4), Sum::Integer(0)]);
+        assert_eq!(
+            summer.summaries().map(|s| s.errors).collect::<Vec<_>>(),
+            [0, 0, 2]
+        );
+    }
+
+    #[test]
+    fn summer_group_by_works() {
+        let mut summer = Summer::builder()
+            .fields([2, 3])
+            .group_by(Some(1.into()))
+            .build();
+        summer
+            .feed_reader("b 1 2\na 3 4\nb 5 OOPS\nc\n\nd 6\n".as_bytes())
+            .unwrap();
+        let groups: Vec<_> = summer
+            .groups()
+            .iter()
+            .map(|g| {
+                let sums: Vec<_> = g.summaries().map(|s| s.sum.clone()).collect();
+                (g.key(), sums)
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                ("b", vec![Sum::Integer(6), Sum::Integer(2)]),
+                ("a", vec![Sum::Integer(3), Sum::Integer(4)]),
+                ("c", vec![Sum::Integer(0), Sum::Integer(0)]),
+                ("d", vec![Sum::Integer(6), Sum::Integer(0)]),
+            ]
+        );
+        assert_eq!(summer.groups()[0].summaries().next().unwrap().lines, 2);
+        assert_eq!(summer.summary().sum, Sum::Integer(15));
+    }
+
+    #[test]
+    fn summer_csv_works() {
+        let mut summer = Summer::builder()
+            .format(InputFormat::Csv)
+            .fields([1, 3])
+            .build();
+        summer
+            .feed_reader("\"1,234\",\"Acme, Inc.\",55\n2,\"a\nb\",0x10\n,\n".as_bytes())
+            .unwrap();
+        let sums: Vec<_> = summer.summaries().map(|s| s.sum.clone()).collect();
+        assert_eq!(sums, [Sum::Integer(1236), Sum::Integer(71)]);
+        assert_eq!(summer.summary().lines, 3);
+    }
+
+    #[test]
+    fn summer_extract_works() {
+        let input = "GET / took 123ms\nGET /a took 7ms\nstartup\nGET /b took 0x10ms\n";
+        let mut summer = Summer::builder()
+            .extract(Some(Regex::new(r"took (\w+?)ms").unwrap()))
+            .build();
+        summer.feed_reader(input.as_bytes()).unwrap();
+        assert_eq!(summer.summary().sum, Sum::Integer(146));
+        assert_eq!(summer.summary().values, 3);
+        assert_eq!(summer.summary().lines, 4);
+
+        let mut summer = Summer::builder()
+            .extract(Some(Regex::new(r"(\S+) took (?P<ms>\d+)ms").unwrap()))
+            .group_by(Some(1.into()))
+            .build();
+        summer.feed_reader(input.as_bytes()).unwrap();
+        assert_eq!(summer.summary().sum, Sum::Integer(130));
+        assert_eq!(summer.groups().len(), 1);
+
+        let mut summer = Summer::builder()
+            .extract(Some(Regex::new(r"\d+").unwrap()))
+            .build();
+        assert_eq!(summer.feed_line("a 12 b 3")[0].raw_str, "12");
+    }
+
+    #[test]
+    fn summer_all_numbers_works() {
+        let mut summer = Summer::builder().all_numbers(true).build();
+        let raw_strs = |entries: Vec<Entry>| -> Vec<String> {
+            entries.into_iter().map(|e| e.raw_str).collect()
+        };
+        assert_eq!(
+            raw_strs(summer.feed_line("3 apples and 4 pears")),
+            ["3", "4"]
+        );
+        assert_eq!(
+            raw_strs(summer.feed_line("took 1,234ms (-0.5s, 1e3 or 0x1F) at v2 on 1.2.3")),
+            ["1,234", "-0.5", "1e3", "0x1F", "1.2"]
+        );
+        assert_eq!(
+            raw_strs(summer.feed_line("no numbers here")),
+            [] as [&str; 0]
+        );
+        assert_eq!(summer.summary().sum, Sum::Float(2272.7));
+        assert_eq!(summer.summary().errors, 0);
+
+        let mut summer = Summer::builder().all_numbers(true).field(2).build();
+        assert_eq!(raw_strs(summer.feed_line("1 a=2,b=3 4")), ["2", "3"]);
+    }
+
+    #[test]
+    fn summer_units_size_works() {
+        let mut summer = Summer::builder().units(Some(Units::Size)).build();
+        summer
+            .feed_reader("1.5G\n512Mi\n20kB\n100\n4 apples\n".as_bytes())
+            .unwrap();
+        assert_eq!(
+            summer.summary().sum,
+            Sum::Float(1.5 * 1073741824.0 + 536870912.0 + 20000.0 + 100.0)
+        );
+        assert_eq!(summer.summary().errors, 1);
+
+        let mut summer = Summer::builder()
+            .units(Some(Units::Size))
+            .exact(true)
+            .build();
+        summer.feed_line("1.5K");
+        assert_eq!(summer.summary().sum, Sum::Decimal(Decimal::new(15360, 1)));
+    }
+
+    #[test]
+    fn summer_units_duration_works() {
+        let mut summer = Summer::builder().units(Some(Units::Duration)).build();
+        summer
+            .feed_reader("250ms\n1.5s\n2h3m4s\n01:02:03\n0.1\n-1m\n1h2x\n".as_bytes())
+            .unwrap();
+        assert_eq!(
+            summer.summary().sum,
+            Sum::Decimal(Decimal::new(11_048_850, 3))
+        );
+        assert_eq!(summer.summary().errors, 1);
+    }
+
+    #[test]
+    fn summer_number_format_works() {
+        let mut summer = Summer::builder()
+            .number_format(Some("de-DE".parse().unwrap()))
+            .exact(true)
+            .build();
+        summer.feed_reader("1.234,56\n0,44\n".as_bytes()).unwrap();
+        assert_eq!(summer.summary().sum, Sum::Decimal(Decimal::new(123500, 2)));
+
+        let mut summer = Summer::builder()
+            .number_format(Some(NumberFormat::new('.', ['\''])))
+            .build();
+        summer.feed_reader("1'234.5\n1,5\n".as_bytes()).unwrap();
+        assert_eq!(summer.summary().sum, Sum::Float(1234.5));
+        assert_eq!(summer.summary().errors, 1);
+    }
+
+    #[test]
+    fn summer_accounting_works() {
+        let mut summer = Summer::builder().exact(true).build();
+        let entries = summer.feed_line("(1,234.00)");
+        assert_eq!(entries[0].n, Sum::Decimal(Decimal::new(-123400, 2)));
+        assert_eq!(entries[0].notes, ["Read parentheses as negative"]);
+        summer
+            .feed_reader("$2,000.50\n1.50-\nEUR 10\n".as_bytes())
+            .unwrap();
+        assert_eq!(summer.summary().sum, Sum::Decimal(Decimal::new(77500, 2)));
+        assert_eq!(summer.summary().errors, 0);
+    }
+
+    #[test]
+    fn summer_inputs_works() {
+        let mut summer = Summer::builder().build();
+        summer.feed_line("100");
+        summer.start_input("a");
+        summer.feed_reader("1\n2\n".as_bytes()).unwrap();
+        summer.start_input("b");
+        summer.feed_reader("3\nx\n\n".as_bytes()).unwrap();
+        let inputs: Vec<_> = summer
+            .inputs()
+            .iter()
+            .map(|input| {
+                let summary = input.summaries().next().unwrap();
+                (
+                    input.key(),
+                    summary.sum.clone(),
+                    summary.lines,
+                    summary.errors,
+                )
+            })
+            .collect();
+        assert_eq!(
+            inputs,
+            [("a", Sum::Integer(3), 2, 0), ("b", Sum::Integer(3), 3, 1)]
+        );
+        assert_eq!(summer.summary().sum, Sum::Integer(106));
+        assert_eq!(summer.summary().lines, 6);
+    }
+
+    #[test]
+    fn summer_diagnostics_works() {
+        let mut summer = Summer::builder().field(2).exact(true).build();
+        summer
+            .feed_reader("a 1,000\nb\nc OOPS\nd 1e3\ne 2.5\n".as_bytes())
+            .unwrap();
+        let diagnostics = summer.diagnostics();
+        let samples = |problem| diagnostics.get(problem).map(|t| t.samples.clone());
+        assert_eq!(samples(Problem::StrippedCommas), Some(vec!["1,000".into()]));
+        assert_eq!(samples(Problem::Skipped), Some(vec!["b".into()]));
+        assert_eq!(samples(Problem::ParseFailure), Some(vec!["OOPS".into()]));
+        assert_eq!(samples(Problem::FloatFallback), Some(vec!["1e3".into()]));
+    }
+
+    #[test]
+    fn summer_json_works() {
+        let path = |s: &str| Field::Path(s.parse().unwrap());
+        let mut summer = Summer::builder()
+            .format(InputFormat::Json)
+            .fields([path(".items[].price"), path(".bytes")])
+            .group_by(Some(path(".host")))
+            .build();
+        summer
+            .feed_reader(
+                concat!(
+                    r#"{"host": "a", "bytes": 10, "items": [{"price": 1.5}, {"price": "2"}]}"#,
+                    "\n",
+                    r#"{"host": "b", "bytes": null, "items": []}"#,
+                    "\n",
+                    "not json\n",
+                    r#"{"bytes": 5}"#,
+                    "\n",
+                    r#"{"host": 1, "bytes": 12345678901234567890123456789012345678901234567890}"#,
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        let sums: Vec<_> = summer.summaries().map(|s| s.sum.clone()).collect();
+        assert_eq!(
+            sums,
+            [
+                Sum::Float(3.5),
+                Sum::BigInt(
+                    "12345678901234567890123456789012345678901234567900"
+                        .parse()
+                        .unwrap()
+                )
+            ]
+        );
+        assert_eq!(summer.summary().lines, 5);
+        let keys: Vec<_> = summer.groups().iter().map(Group::key).collect();
+        assert_eq!(keys, ["a", "b", "1"]);
+
+        let mut summer = Summer::builder().format(InputFormat::Json).field(2).build();
+        assert_eq!(summer.feed_json("[1, 2, 3]")[0].n, Sum::Integer(2));
+        assert_eq!(summer.feed_json("{\"a\": 1}"), []);
+        assert_eq!(summer.feed_json("[1, \"abc\"]"), []);
+        assert_eq!(summer.feed_json("[1, \"0x10\"]")[0].n, Sum::Integer(16));
+        assert_eq!(summer.summary().errors, 0);
+        assert_eq!(
+            summer.diagnostics().get(Problem::Skipped).map(|t| t.count),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn summer_reports_errors() {
+        let mut summer = Summer::builder().build();
+        let entry = &summer.feed_line("OOPS")[0];
+        assert_eq!(entry.n, Sum::Integer(0));
+        assert_eq!(
+            entry.err,
+            Some("Failed to parse (use --radix=hex if hex), treating as 0")
+        );
+        assert_eq!(summer.summary().errors, 1);
+    }
+
+    #[test]
+    fn summer_exact_works() {
+        let mut summer = Summer::builder().exact(true).build();
+        for _ in 0..10 {
+            summer.feed_line("0.1");
+        }
+        assert_eq!(summer.summary().sum.to_string(), "1.0");
+
+        let mut summer = Summer::builder().exact(true).build();
+        summer.feed_reader("1.10\n2.20\n3\n".as_bytes()).unwrap();
+        assert_eq!(summer.summary().sum, Sum::Decimal(Decimal::new(630, 2)));
+        assert_eq!(summer.summary().sum.to_string(), "6.30");
+    }
+
+    #[test]
+    fn summer_is_send_and_sync() {
+        fn check<T: Send + Sync>() {}
+        check::<Summer>();
+        check::<Summary>();
+    }
+
+    #[test]
+    fn sum_cmp_works() {
+        let cmp = |a: Sum, b: Sum| a.cmp_value(&b);
+        assert_eq!(cmp(Sum::Integer(1), Sum::Integer(2)), Some(Ordering::Less));
+        assert_eq!(cmp(Sum::Integer(1), Sum::Float(1.5)), Some(Ordering::Less));
+        assert_eq!(
+            cmp(Sum::Decimal(Decimal::new(15, 1)), Sum::Integer(1)),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            cmp(
+                Sum::BigInt(BigInt::from(i128::MAX) + 1),
+                Sum::Integer(i128::MAX)
+            ),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(cmp(Sum::Float(f64::NAN), Sum::Integer(0)), None);
+        // Equal values of different types are still different Sums.
+        assert_eq!(cmp(Sum::Integer(1), Sum::Float(1.0)), Some(Ordering::Equal));
+        assert_ne!(Sum::Integer(1), Sum::Float(1.0));
+    }
+
+    #[test]
+    fn compensated_sum_works() {
+        let mut plain = Sum::default();
+        let mut compensated = CompensatedSum::default();
+        for n in [
+            Sum::Integer(1),
+            Sum::Float(1e100),
+            Sum::Integer(1),
+            Sum::Float(-1e100),
+        ] {
+            plain += n.clone();
+            compensated += n;
+        }
+        assert_eq!(plain, Sum::Float(0.0));
+        assert_eq!(compensated.sum(), Sum::Float(2.0));
+    }
+
+    #[test]
+    fn summer_precise_float_works() {
+        let mut summer = Summer::builder().precise_float(true).build();
+        for _ in 0..10 {
+            summer.feed_line("0.1");
+        }
+        assert_eq!(summer.summary().sum, Sum::Float(1.0));
+    }
+
+    #[test]
+    fn sum_decimal_mixed_works() {
+        let a = Sum::Decimal(Decimal::new(110, 2));
+        assert_eq!((a.clone() + Sum::Integer(1)).to_string(), "2.10");
+        assert_eq!(a + Sum::Float(0.5), Sum::Float(1.6));
+    }
+
+    #[test]
+    fn summer_feed_reader_works() {
+        let mut summer = Summer::builder()
+            .delimiter(Regex::new(":").unwrap())
+            .field(2)
+            .radix(Radix::Hex)
+            .build();
+        summer.feed_reader("x:a\nx:0x10\n".as_bytes()).unwrap();
+        assert_eq!(summer.summary().sum, Sum::Integer(26));
     }
 }
@@ -0,0 +1,178 @@
+use std::borrow::Cow;
+use std::str::FromStr;
+
+/// Space, no-break space and narrow no-break space, which all separate groups of digits.
+const SPACES: [char; 3] = [' ', '\u{a0}', '\u{202f}'];
+
+/// The characters that numbers use to separate the fractional part and groups of digits, like
+/// `1,234.5` in `en-US`, `1.234,5` in `de-DE` or `1'234.5` in `de-CH`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberFormat {
+    decimal: char,
+    group: Vec<char>,
+}
+
+impl Default for NumberFormat {
+    /// The `en-US` format, like `1,234.5`.
+    fn default() -> Self {
+        NumberFormat::new('.', [','])
+    }
+}
+
+impl NumberFormat {
+    /// Creates a format with the given `decimal` separator and `group` separators. A space group
+    /// separator also matches no-break spaces.
+    pub fn new(decimal: char, group: impl IntoIterator<Item = char>) -> Self {
+        let mut format = NumberFormat {
+            decimal,
+            group: Vec::new(),
+        };
+        format.set_group(group);
+        format
+    }
+
+    /// The decimal separator.
+    pub fn decimal(&self) -> char {
+        self.decimal
+    }
+
+    /// Sets the decimal separator, which stops it from separating groups of digits.
+    pub fn set_decimal(&mut self, decimal: char) {
+        self.decimal = decimal;
+        self.group.retain(|&c| c != decimal);
+    }
+
+    /// The characters that separate groups of digits.
+    pub fn group(&self) -> &[char] {
+        &self.group
+    }
+
+    /// Sets the characters that separate groups of digits. A space also matches no-break spaces,
+    /// and the decimal separator is left out.
+    pub fn set_group(&mut self, group: impl IntoIterator<Item = char>) {
+        self.group = group.into_iter().collect();
+        if self.group.contains(&' ') {
+            self.group.extend(&SPACES[1..]);
+        }
+        let decimal = self.decimal;
+        self.group.retain(|&c| c != decimal);
+    }
+
+    /// A regex that matches a number written in this format, like `-1,234.5e3` or `.5`.
+    pub fn number_pattern(&self) -> String {
+        let decimal = regex::escape(&self.decimal.to_string());
+        let int = match self.group.is_empty() {
+            true => r"\d+".to_string(),
+            false => {
+                let group: String = self
+                    .group
+                    .iter()
+                    .map(|c| regex::escape(&c.to_string()))
+                    .collect();
+                format!(r"\d{{1,3}}(?:[{group}]\d{{3}})+|\d+")
+            }
+        };
+        format!(r"[-+]?(?:(?:{int})(?:{decimal}\d*)?|{decimal}\d+)(?:[eE][-+]?\d+)?")
+    }
+
+    /// Returns `s` with group separators removed and the decimal separator replaced by `.`, so it
+    /// can be parsed like an `en-US` number. Only allocates if there's something to replace.
+    pub fn normalize<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        if !s.contains(|c| self.group.contains(&c) || (c == self.decimal && c != '.')) {
+            return Cow::Borrowed(s);
+        }
+        s.chars()
+            .filter(|c| !self.group.contains(c))
+            .map(|c| if c == self.decimal { '.' } else { c })
+            .collect()
+    }
+}
+
+impl FromStr for NumberFormat {
+    type Err = String;
+
+    /// Looks up the format for a locale like `de-DE`, `de_CH.UTF-8` or `fr`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.split('.').next().unwrap_or(s).replace('_', "-");
+        let name = name.to_ascii_lowercase();
+        let (language, region) = name.split_once('-').unwrap_or((&name, ""));
+        let (decimal, group): (char, &[char]) = match (language, region) {
+            ("c" | "posix", "") => ('.', &[]),
+            ("de" | "it" | "rm", "ch" | "li") => ('.', &['\'', '’']),
+            ("en" | "ja" | "zh" | "ko" | "he" | "hi" | "th" | "ms", _) => ('.', &[',']),
+            (
+                "de" | "es" | "it" | "pt" | "nl" | "da" | "el" | "id" | "tr" | "ro" | "hr" | "sl"
+                | "sr",
+                _,
+            ) => (',', &['.']),
+            (
+                "fr" | "ru" | "uk" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "hu" | "bg"
+                | "et" | "lt" | "lv",
+                _,
+            ) => (',', &SPACES),
+            _ => return Err(format!("unknown locale {s:?}")),
+        };
+        Ok(NumberFormat::new(decimal, group.iter().copied()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(locale: &str, s: &str) -> String {
+        locale
+            .parse::<NumberFormat>()
+            .unwrap()
+            .normalize(s)
+            .into_owned()
+    }
+
+    #[test]
+    fn locales_work() {
+        assert_eq!(normalize("en-US", "1,234.56"), "1234.56");
+        assert_eq!(normalize("de-DE", "1.234,56"), "1234.56");
+        assert_eq!(normalize("de_CH.UTF-8", "1'234.50"), "1234.50");
+        assert_eq!(normalize("fr", "1 234,5"), "1234.5");
+        assert_eq!(normalize("fr-FR", "1\u{202f}234\u{a0}567,5"), "1234567.5");
+        assert_eq!(normalize("C", "1,234.5"), "1,234.5");
+        let format = NumberFormat::default();
+        assert!(matches!(
+            format.normalize("1234.5"),
+            Cow::Borrowed("1234.5")
+        ));
+        assert!("xx-YY".parse::<NumberFormat>().is_err());
+    }
+
+    #[test]
+    fn custom_separators_work() {
+        let mut format = NumberFormat::default();
+        assert_eq!(format.normalize("1,234.5"), "1234.5");
+        format.set_decimal(',');
+        format.set_group([' ']);
+        assert_eq!(format.group(), [' ', '\u{a0}', '\u{202f}']);
+        assert_eq!(format.normalize("1\u{a0}234,5"), "1234.5");
+    }
+
+    #[test]
+    fn decimal_is_not_a_group() {
+        let mut format = NumberFormat::default();
+        format.set_decimal(',');
+        assert_eq!(format.group(), []);
+        assert_eq!(format.normalize("1,5"), "1.5");
+        format.set_group([',', '.']);
+        assert_eq!(format.group(), ['.']);
+    }
+
+    #[test]
+    fn number_pattern_works() {
+        let re = |format: NumberFormat| regex::Regex::new(&format.number_pattern()).unwrap();
+        let find = |re: &regex::Regex, s| re.find(s).map(|m| m.as_str().to_string());
+        let en = re(NumberFormat::default());
+        assert_eq!(find(&en, "x -1,234.5e3 y").as_deref(), Some("-1,234.5e3"));
+        let de = re("de-DE".parse().unwrap());
+        assert_eq!(find(&de, "total 1.234,5 EUR").as_deref(), Some("1.234,5"));
+        let c = re("C".parse().unwrap());
+        assert_eq!(find(&c, "1,5").as_deref(), Some("1"));
+    }
+}
@@ -1,230 +1,781 @@
-use clap::{Parser, ValueEnum};
+use clap::{CommandFactory, Parser, ValueEnum};
 use colored::Colorize;
 use regex::Regex;
-use std::io::{self, BufRead, BufReader};
-use sumcol::Sum;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::io::{self, BufRead, BufReader, IsTerminal};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+use sumcol::{
+    Agg, Diagnostics, DurationFormat, Field, Follower, Group, InputFormat, JsonPath, NumberFormat,
+    Problem, QuantileMode, Radix, Record, Records, SizeFormat, Sum, Summary, Summer, Units,
+};
 
-/// How to interpret numeric input.
+/// How often --follow checks the file for new lines.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The most fields that a range like `2-6` can have, so that a typo can't allocate millions.
+const MAX_RANGE_FIELDS: usize = 10_000;
+
+/// The exit status when --strict or --max-errors fails, EX_DATAERR from sysexits.h.
+const EXIT_PARSE_ERROR: i32 = 65;
+
+/// A field index, an inclusive range of them like `2-6`, a column name from the --header line, or
+/// a path like `.a.b` into --json input. Ranges keep their text, since a column with that name
+/// is used instead.
+#[derive(Clone, Debug)]
+enum FieldSpec {
+    Range(RangeInclusive<usize>, String),
+    Name(String),
+    Path(JsonPath),
+}
+
+impl FromStr for FieldSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with('.') {
+            return s.parse().map(FieldSpec::Path);
+        }
+        let range = match s.split_once('-') {
+            Some((start, end)) => start.parse().and_then(|start| Ok(start..=end.parse()?)),
+            None => s.parse().map(|n| n..=n),
+        };
+        match range {
+            Ok(range) if range.is_empty() => Err(format!(
+                "invalid field range {s:?}: {} is after {}",
+                range.start(),
+                range.end()
+            )),
+            Ok(range) if range.end() - range.start() >= MAX_RANGE_FIELDS => Err(format!(
+                "invalid field range {s:?}: more than {MAX_RANGE_FIELDS} fields"
+            )),
+            Ok(range) => Ok(FieldSpec::Range(range, s.to_string())),
+            Err(_) if s.is_empty() => Err("empty field".to_string()),
+            Err(_) => Ok(FieldSpec::Name(s.to_string())),
+        }
+    }
+}
+
+/// Resolves `specs` to fields, looking up names in the `header` columns, which take precedence
+/// over indexes and ranges. Paths are only allowed for `json` input.
+fn resolve_fields(
+    specs: &[FieldSpec],
+    header: Option<&[&str]>,
+    json: bool,
+) -> Result<Vec<Field>, String> {
+    let column = |name: &str| header?.iter().position(|column| *column == name);
+    let mut fields = Vec::new();
+    for spec in specs {
+        match (spec, header) {
+            (FieldSpec::Range(range, name), _) => match column(name) {
+                Some(index) => fields.push(Field::Index(index + 1)),
+                None => fields.extend(range.clone().map(Field::Index)),
+            },
+            (FieldSpec::Path(path), _) if json => fields.push(Field::Path(path.clone())),
+            (FieldSpec::Path(path), _) => {
+                return Err(format!(
+                    "field \"{path}\" is a JSON path, which requires --json"
+                ));
+            }
+            (FieldSpec::Name(name), None) => {
+                return Err(format!("field {name:?} is a name, which requires --header"));
+            }
+            (FieldSpec::Name(name), Some(header)) => {
+                let Some(index) = column(name) else {
+                    return Err(format!(
+                        "unknown field {name:?} (available columns: {})",
+                        header.join(", ")
+                    ));
+                };
+                fields.push(Field::Index(index + 1));
+            }
+        }
+    }
+    Ok(fields)
+}
+
+/// How to sort the groups when using --group-by.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
-enum Radix {
-    /// Decimal unless the value has a leading 0x prefix (default).
-    Auto,
-    /// Always hex; values with a 0x prefix have it stripped first.
+enum GroupSort {
+    /// By key, in ascending order.
+    Key,
+    /// By the sum of the first field, largest first.
+    Sum,
+}
+
+/// The radix to print numbers in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputRadix {
+    /// Decimal.
+    Dec,
+    /// Lowercase hex, like 0xff.
     Hex,
-    /// Always decimal; 0x-prefixed values fail to parse.
-    Decimal,
+    /// Uppercase hex, like 0xFF.
+    #[value(name = "HEX")]
+    UpperHex,
+    /// Octal, like 0o377.
+    Oct,
+    /// Binary, like 0b11111111.
+    Bin,
+}
+
+impl OutputRadix {
+    fn prefix(self) -> &'static str {
+        match self {
+            OutputRadix::Dec => "",
+            OutputRadix::Hex | OutputRadix::UpperHex => "0x",
+            OutputRadix::Oct => "0o",
+            OutputRadix::Bin => "0b",
+        }
+    }
+}
+
+impl From<Radix> for OutputRadix {
+    fn from(radix: Radix) -> Self {
+        match radix {
+            Radix::Auto | Radix::Decimal => OutputRadix::Dec,
+            Radix::Hex => OutputRadix::UpperHex,
+            Radix::Octal => OutputRadix::Oct,
+            Radix::Binary => OutputRadix::Bin,
+        }
+    }
+}
+
+/// How to report problems with the input, like values that fail to parse.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum Warnings {
+    /// A summary when stderr is a terminal, otherwise each one.
+    Auto,
+    /// A summary at the end, with a few samples of each kind of problem.
+    Summary,
+    /// A warning for each problem as it happens.
+    Each,
+}
+
+/// What to print for each line with --running.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum Running {
+    /// The line, followed by a tab and the running total.
+    Lines,
+    /// Only the running total, for each line with a value.
+    Totals,
 }
 
 /// Sum a column of numbers from text input.
 ///
 /// Examples:
 ///   ls -l | sumcol -f5
+///   sumcol -f2-4,7 data.txt
+///   sumcol -e 'took (\d+)ms' app.log
+///   sumcol --json -f .response.bytes access.jsonl
 ///
 #[derive(Parser, Debug)]
 #[command(version, verbatim_doc_comment)]
 struct Args {
-    /// The field to sum. If not specified, uses the full line.
-    #[arg(long, short, default_value("0"))]
-    field: usize,
+    /// The field to sum. If not specified, uses the full line. Accepts a list of fields and
+    /// ranges, like 3,5,7 or 2-6, to print one total per field, column names with --header, and
+    /// paths like .items[].price with --json.
+    #[arg(long, short, default_value("0"), value_delimiter = ',')]
+    field: Vec<FieldSpec>,
+
+    /// Read the first line of each input as column names, which --field and --group-by accept.
+    #[arg(long, conflicts_with = "json")]
+    header: bool,
 
-    /// How to interpret numeric input.
-    #[arg(long, value_enum, default_value_t = Radix::Auto)]
+    /// How to interpret numeric input: auto (decimal unless prefixed with 0x, 0o or 0b), hex,
+    /// decimal, octal or binary.
+    #[arg(long, default_value_t = Radix::Auto)]
     radix: Radix,
 
+    /// The radix to print totals in. Defaults to the --radix of the input, or decimal.
+    #[arg(long, value_enum)]
+    output_radix: Option<OutputRadix>,
+
+    /// Pad printed numbers with spaces to at least this many characters, including any sign and
+    /// radix prefix.
+    #[arg(long, value_name = "N")]
+    width: Option<usize>,
+
+    /// Pad with zeros after the sign and radix prefix instead of spaces, like 0x00ff.
+    #[arg(long, requires = "width")]
+    zero_pad: bool,
+
+    /// The locale whose number format the input uses, like de-DE for 1.234,5 or de-CH for
+    /// 1'234.5. Defaults to en-US, but warns when commas are stripped.
+    #[arg(long)]
+    locale: Option<NumberFormat>,
+
+    /// The decimal separator, overriding the one from --locale.
+    #[arg(long, value_name = "CHAR")]
+    decimal_sep: Option<char>,
+
+    /// The characters that separate groups of digits, overriding the ones from --locale. A space
+    /// also matches no-break spaces.
+    #[arg(long, value_name = "CHARS")]
+    group_sep: Option<String>,
+
+    /// The units of the input, size or duration, which are converted to a base unit (bytes or
+    /// seconds) before summing.
+    #[arg(long)]
+    units: Option<Units>,
+
+    /// Print sizes in human-readable form, with binary (1.5GiB) or decimal (1.6GB) suffixes.
+    #[arg(long, requires = "units")]
+    size_format: Option<SizeFormat>,
+
+    /// How to print durations: compact (1d2h3m4.5s), clock (26:03:04.5) or seconds.
+    #[arg(long, default_value_t = DurationFormat::Compact, requires = "units")]
+    duration_format: DurationFormat,
+
+    /// Sum decimal values exactly, keeping the number of decimal places of the inputs.
+    #[arg(long)]
+    exact: bool,
+
+    /// Sum floating point values with compensated summation, which is more accurate but slower.
+    #[arg(long)]
+    precise_float: bool,
+
+    /// Sum separately for each distinct value of this field, printing a row per group.
+    #[arg(long, short, value_name = "FIELD")]
+    group_by: Option<FieldSpec>,
+
+    /// How to sort groups. Defaults to the order they first appear in the input.
+    #[arg(long, value_enum, requires = "group_by")]
+    sort: Option<GroupSort>,
+
+    /// Only print the first N groups, sorting by sum unless --sort is given.
+    #[arg(long, value_name = "N", requires = "group_by")]
+    top: Option<usize>,
+
     /// The regex on which to split fields.
     #[arg(long, short, default_value(r"\s+"))]
     delimiter: Regex,
 
+    /// Sum the first capture group of this regex, or its first named group, instead of a field.
+    /// Lines that don't match are skipped.
+    #[arg(long, short = 'e', value_name = "REGEX", conflicts_with_all = ["field", "json"])]
+    extract: Option<Regex>,
+
+    /// Sum every number found in the field, like 3 and 4 in "3 apples and 4 pears", instead of
+    /// parsing the whole field as one number.
+    #[arg(long, conflicts_with = "json")]
+    all_numbers: bool,
+
+    /// Read the input as CSV, with quoted fields that may contain commas and newlines.
+    #[arg(long, conflicts_with_all = ["tsv", "delimiter"])]
+    csv: bool,
+
+    /// Read the input as tab-separated values, quoted like CSV.
+    #[arg(long, conflicts_with = "delimiter")]
+    tsv: bool,
+
+    /// Read the input as JSON Lines, one JSON value per line, selecting fields by path.
+    #[arg(long, conflicts_with_all = ["csv", "tsv", "delimiter"])]
+    json: bool,
+
+    /// Aggregations to print instead of the sum: sum, count, mean, min, max, variance, stddev,
+    /// stderr, median, or a percentile like p99.9. May be repeated.
+    #[arg(long, value_delimiter = ',')]
+    agg: Vec<Agg>,
+
+    /// How to compute percentiles: exact, or sketch to estimate them in bounded memory.
+    #[arg(long, default_value_t = QuantileMode::Exact)]
+    percentiles: QuantileMode,
+
+    /// Fail with exit status 65 if a value can't be parsed, instead of counting it as 0, or if
+    /// a line, field or JSON value is skipped because it's missing or invalid.
+    #[arg(long)]
+    strict: bool,
+
+    /// Like --strict, but only fail once more than N values can't be parsed or are skipped.
+    #[arg(long, value_name = "N")]
+    max_errors: Option<usize>,
+
+    /// How to report problems with the input.
+    #[arg(long, value_enum, default_value_t = Warnings::Auto)]
+    warnings: Warnings,
+
     /// Print each number that's being summed, along with some metadata
     #[arg(long, short = 'v')]
     verbose: bool,
 
+    /// Print the running total after each line, instead of only the total at the end.
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "lines",
+        conflicts_with_all = ["verbose", "group_by", "per_file", "agg"]
+    )]
+    running: Option<Running>,
+
+    /// Print a subtotal for each file, followed by its number of lines and values that failed to
+    /// parse, and then the total, like wc.
+    #[arg(long, conflicts_with = "group_by")]
+    per_file: bool,
+
+    /// Keep reading the file as it's appended to, like tail -f, and print the total again as it
+    /// changes. Follows the new file when it's rotated, and starts over when it's truncated.
+    #[arg(long, requires = "files", conflicts_with_all = ["csv", "tsv", "header", "per_file"])]
+    follow: bool,
+
+    /// With --follow, print the total at most once per this many seconds, instead of whenever
+    /// new lines are read.
+    #[arg(long, value_name = "SECONDS", value_parser = parse_interval, requires = "follow")]
+    interval: Option<Duration>,
+
     /// Files to read input from, otherwise uses stdin.
     #[arg(trailing_var_arg = true)]
     pub files: Vec<String>,
 }
 
-fn fmt_sum(sum: Sum, radix: Radix) -> String {
-    match radix {
-        Radix::Hex => format!("{sum:#X}"),
-        _ => format!("{sum}"),
+/// Parses a positive number of seconds, like 0.5, for --interval.
+fn parse_interval(s: &str) -> Result<Duration, String> {
+    let secs: f64 = s
+        .parse()
+        .map_err(|err: std::num::ParseFloatError| err.to_string())?;
+    match Duration::try_from_secs_f64(secs) {
+        Ok(interval) if interval.is_zero() => Err("must be more than 0".to_string()),
+        interval => interval.map_err(|err| err.to_string()),
     }
 }
 
-fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn")),
-        )
-        .without_time()
-        .with_writer(std::io::stderr)
-        .init();
-    let args = Args::parse();
-    tracing::debug!(?args, "Starting sumcol");
+/// How to print values that have --units.
+#[derive(Copy, Clone, Debug)]
+enum UnitsFormat {
+    Size(SizeFormat),
+    Duration(DurationFormat),
+}
 
-    let readers: Vec<Box<dyn BufRead>> = if args.files.is_empty() {
-        vec![Box::new(BufReader::new(io::stdin()))]
-    } else {
-        args.files
-            .iter()
-            .map(|f| fs_err::File::open(f).map(|f| Box::new(BufReader::new(f)) as Box<dyn BufRead>))
-            .collect::<Result<_, _>>()?
-    };
+impl UnitsFormat {
+    fn new(args: &Args) -> Option<Self> {
+        match args.units? {
+            Units::Size => Some(UnitsFormat::Size(args.size_format?)),
+            Units::Duration => Some(UnitsFormat::Duration(args.duration_format)),
+        }
+    }
+}
+
+/// How to print numbers.
+#[derive(Copy, Clone, Debug)]
+struct Output {
+    radix: OutputRadix,
+    width: usize,
+    zero_pad: bool,
+    units: Option<UnitsFormat>,
+}
 
-    let mut sum = Sum::Integer(0);
-    for line in readers.into_iter().flat_map(|r| r.lines()) {
-        let line = line?.trim().to_string();
-        tracing::debug!(?line, "Read line");
-        if line.is_empty() {
-            continue;
+impl Output {
+    fn new(args: &Args) -> Self {
+        Output {
+            radix: args.output_radix.unwrap_or(args.radix.into()),
+            width: args.width.unwrap_or(0),
+            zero_pad: args.zero_pad,
+            units: UnitsFormat::new(args),
         }
-        let raw_str = match args.field {
-            0 => Some(line.as_str()),
-            f => args.delimiter.split(&line).nth(f - 1),
-        };
-        let Some(raw_str) = raw_str else {
-            tracing::warn!(
-                field = args.field,
-                line,
-                "Field index out of range, skipping"
-            );
-            continue;
+    }
+
+    /// Pads `s` to the output width.
+    fn pad(self, s: String) -> String {
+        let Some(fill) = self.width.checked_sub(s.chars().count()) else {
+            return s;
         };
-        let trimmed = raw_str.trim();
-        let clean_str = trimmed.replace(',', "");
-        if clean_str != trimmed {
-            tracing::warn!(
-                original = trimmed,
-                clean = clean_str.as_str(),
-                "Stripped commas from value"
-            );
+        if !self.zero_pad {
+            return format!("{}{s}", " ".repeat(fill));
         }
-        let (clean_str, radix) = match (args.radix, clean_str.strip_prefix("0x")) {
-            (Radix::Decimal, _) => (clean_str.as_str(), Radix::Decimal),
-            (_, Some(s)) => (s, Radix::Hex),
-            (Radix::Hex, None) => (clean_str.as_str(), Radix::Hex),
-            (Radix::Auto, None) => (clean_str.as_str(), Radix::Decimal),
+        // The zeros go between the sign and prefix and the digits, like printf's %#08x.
+        let sign = s.len() - s.trim_start_matches('-').len();
+        let prefix = match s[sign..].starts_with(self.radix.prefix()) {
+            true => sign + self.radix.prefix().len(),
+            false => sign,
         };
-        let (n, err) = match parse_value(clean_str, radix) {
-            Ok(n) => (n, None),
-            Err(msg) => {
-                tracing::warn!(?clean_str, "{msg}");
-                (Sum::Integer(0), Some(msg))
-            }
-        };
-        sum += n;
-        if args.verbose {
-            let meta = format!("# n={n:?} sum={sum:?} radix={radix:?} raw_str={raw_str:?}").cyan();
-            let err_str = err
-                .map(|e| format!(" err={e:?}").red().to_string())
-                .unwrap_or_default();
-            println!("{}\t {meta}{err_str}", fmt_sum(n, radix));
-        }
+        format!("{}{}{}", &s[..prefix], "0".repeat(fill), &s[prefix..])
     }
+}
 
-    if args.verbose {
-        println!("{}", "==".cyan());
+fn fmt_sum(sum: &Sum, output: Output) -> String {
+    let s = match (output.units, output.radix) {
+        (Some(UnitsFormat::Size(size)), _) => size.format(sum),
+        (Some(UnitsFormat::Duration(duration)), _) => duration.format(sum),
+        (None, OutputRadix::Dec) => format!("{sum}"),
+        (None, OutputRadix::Hex) => format!("{sum:#x}"),
+        (None, OutputRadix::UpperHex) => format!("{sum:#X}"),
+        (None, OutputRadix::Oct) => format!("{sum:#o}"),
+        (None, OutputRadix::Bin) => format!("{sum:#b}"),
+    };
+    output.pad(s)
+}
+
+fn fmt_agg(agg: Agg, summary: &Summary, output: Output) -> String {
+    match agg.compute(summary) {
+        None => "-".to_string(),
+        Some(n) if agg == Agg::Count => output.pad(n.to_string()),
+        // Variances are in squared units, which can't be printed with the input's units.
+        Some(n) if matches!(agg, Agg::Variance | Agg::PopulationVariance) => fmt_sum(
+            &n,
+            Output {
+                units: None,
+                ..output
+            },
+        ),
+        Some(n) => fmt_sum(&n, output),
     }
-    println!("{}", fmt_sum(sum, args.radix));
+}
 
-    Ok(())
+/// Formats `agg` for each of `summaries`, separated by tabs.
+fn fmt_row<'a>(agg: Agg, summaries: impl Iterator<Item = &'a Summary>, output: Output) -> String {
+    summaries
+        .map(|summary| fmt_agg(agg, summary, output))
+        .collect::<Vec<_>>()
+        .join("\t")
 }
 
-/// Parses `s` according to the given `radix`. With `Radix::Hex`, only integers
-/// are accepted (no float fallback) -- this keeps hex mode strict so users can
-/// trust that a successful parse means the value was treated as hex. `Radix::Auto`
-/// is treated the same as `Radix::Decimal` here; the caller is responsible for
-/// resolving 0x-prefix detection before calling.
-fn parse_value(s: &str, radix: Radix) -> Result<Sum, &'static str> {
-    let hex = radix == Radix::Hex;
-    if let Ok(n) = i128::from_str_radix(s, if hex { 16 } else { 10 }) {
-        return Ok(Sum::Integer(n));
-    }
-    if hex {
-        return Err("Failed to parse as hex, treating as 0");
-    }
-    if let Ok(n) = s.parse::<f64>() {
-        if !s.contains(['.', 'e', 'E']) {
-            tracing::warn!(
-                clean_str = s,
-                "Value too large for integer, using float (may lose precision)"
-            );
+/// Prints a report of the problems with the input to stderr, with a few samples of each.
+fn report(diagnostics: &Diagnostics) {
+    if diagnostics.is_empty() {
+        return;
+    }
+    eprintln!(
+        "{} problems with the input (use --warnings=each to see every one):",
+        "warning:".yellow().bold()
+    );
+    for (problem, tally) in diagnostics.iter() {
+        let mut samples: Vec<String> = tally.samples.iter().map(|s| format!("{s:?}")).collect();
+        if tally.count > samples.len() {
+            samples.push("...".to_string());
         }
-        return Ok(Sum::Float(n));
+        eprintln!("  {} {problem}: {}", tally.count, samples.join(", "));
     }
-    Err("Failed to parse (use --radix=hex if hex), treating as 0")
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn parse_integer_decimal() {
-        assert_eq!(parse_value("42", Radix::Decimal), Ok(Sum::Integer(42)));
+/// The text of `record`, with fields separated by tabs.
+fn record_text(record: &Record) -> Cow<'_, str> {
+    match record {
+        Record::Line(line) | Record::Json(line) => Cow::Borrowed(line),
+        Record::Fields(fields) => Cow::Owned(fields.join("\t")),
     }
+}
 
-    #[test]
-    fn parse_integer_hex() {
-        assert_eq!(parse_value("FF", Radix::Hex), Ok(Sum::Integer(255)));
-    }
+/// Formats a --per-file row like `wc` does: each of `aggs` for each of `summaries`, then the
+/// number of lines and errors, then the `name` of the input.
+fn fmt_subtotal<'a>(
+    aggs: &[Agg],
+    summaries: impl Iterator<Item = &'a Summary>,
+    name: &str,
+    output: Output,
+) -> String {
+    let summaries: Vec<&Summary> = summaries.collect();
+    let mut row: Vec<_> = aggs
+        .iter()
+        .map(|agg| fmt_row(*agg, summaries.iter().copied(), output))
+        .collect();
+    let lines = summaries.first().map_or(0, |summary| summary.lines);
+    let errors: usize = summaries.iter().map(|summary| summary.errors).sum();
+    row.extend([lines.to_string(), errors.to_string(), name.to_string()]);
+    row.join("\t")
+}
 
-    #[test]
-    fn parse_negative_integer() {
-        assert_eq!(parse_value("-5", Radix::Decimal), Ok(Sum::Integer(-5)));
+/// Prints the totals of everything `summer` has been fed.
+fn print_totals(args: &Args, summer: &Summer, output: Output) {
+    if args.verbose {
+        println!("{}", "==".cyan());
     }
-
-    #[test]
-    fn parse_float() {
-        assert_eq!(parse_value("1.5", Radix::Decimal), Ok(Sum::Float(1.5)));
+    let aggs = if args.agg.is_empty() {
+        vec![Agg::Sum]
+    } else {
+        args.agg.clone()
+    };
+    if args.running.is_some() {
+        // The last running total was the total.
+    } else if args.per_file {
+        for input in summer.inputs() {
+            println!(
+                "{}",
+                fmt_subtotal(&aggs, input.summaries(), input.key(), output)
+            );
+        }
+        println!(
+            "{}",
+            fmt_subtotal(&aggs, summer.summaries(), "total", output)
+        );
+    } else if args.group_by.is_some() {
+        let mut groups: Vec<&Group> = summer.groups().iter().collect();
+        match args.sort.or(args.top.map(|_| GroupSort::Sum)) {
+            Some(GroupSort::Key) => groups.sort_by(|a, b| a.key().cmp(b.key())),
+            Some(GroupSort::Sum) => groups.sort_by(|a, b| {
+                let sum = |g: &Group| {
+                    g.summaries()
+                        .next()
+                        .map_or(Sum::Integer(0), |s| s.sum.clone())
+                };
+                sum(b).cmp_value(&sum(a)).unwrap_or(Ordering::Equal)
+            }),
+            None => {}
+        }
+        groups.truncate(args.top.unwrap_or(usize::MAX));
+        for group in groups {
+            let row: Vec<_> = aggs
+                .iter()
+                .map(|agg| fmt_row(*agg, group.summaries(), output))
+                .collect();
+            println!("{}\t{}", group.key(), row.join("\t"));
+        }
+    } else {
+        match aggs.as_slice() {
+            [agg] => println!("{}", fmt_row(*agg, summer.summaries(), output)),
+            aggs => {
+                for agg in aggs {
+                    let row = fmt_row(*agg, summer.summaries(), output);
+                    println!("{agg}\t{row}");
+                }
+            }
+        }
     }
+}
 
-    #[test]
-    fn parse_negative_float() {
-        assert_eq!(parse_value("-1.5", Radix::Decimal), Ok(Sum::Float(-1.5)));
-    }
+fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let warnings = match args.warnings {
+        // Following never ends, so there's no end to summarize problems at.
+        Warnings::Auto if args.follow => Warnings::Each,
+        Warnings::Auto if io::stderr().is_terminal() => Warnings::Summary,
+        Warnings::Auto => Warnings::Each,
+        warnings => warnings,
+    };
+    // In summary mode, warnings are reported together at the end instead of logged.
+    let level = match warnings {
+        Warnings::Summary => "error",
+        _ => "warn",
+    };
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(level)),
+        )
+        .without_time()
+        .with_writer(std::io::stderr)
+        .init();
+    tracing::debug!(?args, "Starting sumcol");
 
-    #[test]
-    fn parse_scientific_notation() {
-        assert_eq!(parse_value("3e0", Radix::Decimal), Ok(Sum::Float(3.0)));
+    let format = match (args.csv, args.tsv, args.json) {
+        (true, _, _) => InputFormat::Csv,
+        (_, true, _) => InputFormat::Tsv,
+        (_, _, true) => InputFormat::Json,
+        _ => InputFormat::Text,
+    };
+    if args.follow && args.files.len() > 1 {
+        Args::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--follow takes a single file",
+            )
+            .exit();
     }
+    let readers: Vec<Box<dyn BufRead>> = if args.follow {
+        // The file is read by a Follower instead.
+        Vec::new()
+    } else if args.files.is_empty() {
+        vec![Box::new(BufReader::new(io::stdin()))]
+    } else {
+        args.files
+            .iter()
+            .map(|f| fs_err::File::open(f).map(|f| Box::new(BufReader::new(f)) as Box<dyn BufRead>))
+            .collect::<Result<_, _>>()?
+    };
 
-    #[test]
-    fn parse_float_in_hex_mode_fails() {
-        assert_eq!(
-            parse_value("1.5", Radix::Hex),
-            Err("Failed to parse as hex, treating as 0")
-        );
+    let mut inputs: Vec<_> = readers
+        .into_iter()
+        .map(|r| Records::new(r, format))
+        .collect();
+    let mut header = Vec::new();
+    if args.header {
+        // Every input starts with a header, but only the first one is used for names.
+        for (i, records) in inputs.iter_mut().enumerate() {
+            let record = records.next().transpose()?;
+            if i == 0 {
+                header = match record {
+                    Some(Record::Line(line)) => args
+                        .delimiter
+                        .split(line.trim())
+                        .map(String::from)
+                        .collect(),
+                    Some(Record::Fields(fields)) => fields,
+                    Some(Record::Json(_)) | None => Vec::new(),
+                };
+            }
+        }
     }
-
-    #[test]
-    fn parse_invalid_decimal() {
-        assert_eq!(
-            parse_value("OOPS", Radix::Decimal),
-            Err("Failed to parse (use --radix=hex if hex), treating as 0")
-        );
+    let header: Vec<&str> = header.iter().map(|column| column.trim()).collect();
+    let header = args.header.then_some(header.as_slice());
+    let resolved = resolve_fields(&args.field, header, args.json).and_then(|fields| {
+        let group_by = match &args.group_by {
+            Some(spec) => {
+                match resolve_fields(std::slice::from_ref(spec), header, args.json)?[..] {
+                    [ref field] => Some(field.clone()),
+                    _ => return Err("--group-by takes a single field".to_string()),
+                }
+            }
+            None => None,
+        };
+        Ok((fields, group_by))
+    });
+    let (fields, group_by) = match resolved {
+        Ok(resolved) => resolved,
+        Err(msg) => Args::command()
+            .error(clap::error::ErrorKind::InvalidValue, msg)
+            .exit(),
+    };
+    let number_format = match (&args.locale, args.decimal_sep, &args.group_sep) {
+        (None, None, None) => None,
+        (locale, decimal_sep, group_sep) => {
+            let mut format = locale.clone().unwrap_or_default();
+            if let Some(decimal_sep) = decimal_sep {
+                format.set_decimal(decimal_sep);
+            }
+            if let Some(group_sep) = group_sep {
+                format.set_group(group_sep.chars());
+            }
+            Some(format)
+        }
+    };
+    let output = Output::new(&args);
+    let wants_stats = args.agg.iter().any(Agg::needs_stats);
+    let wants_quantiles = args.agg.iter().any(Agg::needs_quantiles);
+    let mut summer = Summer::builder()
+        .fields(fields.iter().cloned())
+        .group_by(group_by)
+        .delimiter(args.delimiter.clone())
+        .extract(args.extract.clone())
+        .all_numbers(args.all_numbers)
+        .units(args.units)
+        .number_format(number_format)
+        .radix(args.radix)
+        .exact(args.exact)
+        .precise_float(args.precise_float)
+        .stats(wants_stats)
+        .quantiles(wants_quantiles.then_some(args.percentiles))
+        .build();
+    let names: Vec<&str> = match args.files.is_empty() {
+        true => vec!["<stdin>"],
+        false => args.files.iter().map(String::as_str).collect(),
+    };
+    let max_errors = args.max_errors.or(args.strict.then_some(0));
+    let mut errors = 0;
+    // Feeds `record` to `summer`, printing it for --verbose or --running, and failing for --strict.
+    let mut feed = |summer: &mut Summer, name: &str, line: u64, record: &Record| {
+        let skipped = |summer: &Summer| {
+            let tally = summer.diagnostics().get(Problem::Skipped);
+            tally.map_or(0, |tally| tally.count)
+        };
+        let skipped_before = skipped(summer);
+        let entries = summer.feed(record);
+        let mut failed = None;
+        for entry in &entries {
+            if args.verbose {
+                let mut meta = format!(
+                    "# n={:?} sum={:?} radix={:?} raw_str={:?}",
+                    entry.n, entry.sum, entry.radix, entry.raw_str
+                );
+                if fields.len() > 1 {
+                    meta += &format!(" field={}", entry.field);
+                }
+                if let Some(group) = &entry.group {
+                    meta += &format!(" group={group:?}");
+                }
+                if !entry.notes.is_empty() {
+                    meta += &format!(" note={:?}", entry.notes.join(", "));
+                }
+                let meta = meta.cyan();
+                let err_str = entry
+                    .err
+                    .map(|e| format!(" err={e:?}").red().to_string())
+                    .unwrap_or_default();
+                println!(
+                    "{}\t {meta}{err_str}",
+                    fmt_sum(
+                        &entry.n,
+                        Output {
+                            radix: args.output_radix.unwrap_or(entry.radix.into()),
+                            ..output
+                        }
+                    )
+                );
+            }
+            if entry.err.is_some() {
+                errors += 1;
+                failed.get_or_insert(&entry.raw_str);
+            }
+        }
+        errors += skipped(summer) - skipped_before;
+        if let Some(max_errors) = max_errors.filter(|&max| errors > max) {
+            let budget = match max_errors {
+                0 => String::new(),
+                n => format!(" (more than --max-errors {n})"),
+            };
+            let problem = match failed {
+                Some(raw_str) => format!("failed to parse {raw_str:?}"),
+                None => format!("skipped {:?}", record_text(record)),
+            };
+            eprintln!("{} {name}:{line}: {problem}{budget}", "error:".red().bold());
+            std::process::exit(EXIT_PARSE_ERROR);
+        }
+        match args.running {
+            Some(Running::Lines) => println!(
+                "{}\t{}",
+                record_text(record),
+                fmt_row(Agg::Sum, summer.summaries(), output)
+            ),
+            Some(Running::Totals) if !entries.is_empty() => {
+                println!("{}", fmt_row(Agg::Sum, summer.summaries(), output))
+            }
+            _ => {}
+        }
+    };
+    for (name, mut records) in names.into_iter().zip(inputs) {
+        if args.per_file {
+            summer.start_input(name);
+        }
+        while let Some(record) = records.next() {
+            feed(&mut summer, name, records.line(), &record?);
+        }
     }
 
-    #[test]
-    fn parse_empty_string() {
-        assert_eq!(
-            parse_value("", Radix::Decimal),
-            Err("Failed to parse (use --radix=hex if hex), treating as 0")
-        );
+    if args.follow {
+        let path = &args.files[0];
+        let mut follower = Follower::open(path)?;
+        let mut printed: Option<Instant> = None;
+        let mut changed = true;
+        loop {
+            for (line, text) in follower.poll()? {
+                let record = match format {
+                    InputFormat::Json => Record::Json(text),
+                    _ => Record::Line(text),
+                };
+                feed(&mut summer, path, line, &record);
+                changed = true;
+            }
+            let due = match (args.interval, printed) {
+                (Some(interval), Some(printed)) => printed.elapsed() >= interval,
+                _ => true,
+            };
+            if changed && due {
+                summer.finish();
+                print_totals(&args, &summer, output);
+                printed = Some(Instant::now());
+                changed = false;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
     }
-
-    #[test]
-    fn parse_overflow_falls_back_to_float() {
-        let result = parse_value("999999999999999999999999999999999999999999", Radix::Decimal);
-        assert!(matches!(result, Ok(Sum::Float(_))));
+    summer.finish();
+    print_totals(&args, &summer, output);
+    if warnings == Warnings::Summary {
+        report(summer.diagnostics());
     }
 
-    #[test]
-    fn parse_invalid_hex() {
-        assert_eq!(
-            parse_value("GG", Radix::Hex),
-            Err("Failed to parse as hex, treating as 0")
-        );
-    }
+    Ok(())
 }
@@ -0,0 +1,311 @@
+use crate::Sum;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::f64::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+/// How to compute percentiles.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum QuantileMode {
+    /// Buffer every value and compute exact percentiles.
+    Exact,
+    /// Estimate percentiles with a bounded-memory t-digest sketch.
+    Sketch,
+}
+
+impl FromStr for QuantileMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "exact" => Ok(QuantileMode::Exact),
+            "sketch" => Ok(QuantileMode::Sketch),
+            _ => Err(format!(
+                "unknown percentile mode {s:?} (expected exact or sketch)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for QuantileMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuantileMode::Exact => write!(f, "exact"),
+            QuantileMode::Sketch => write!(f, "sketch"),
+        }
+    }
+}
+
+/// Tracks the values needed to compute percentiles, either exactly or approximately.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantiles(Inner);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Inner {
+    /// Every value, and whether they're sorted. See [`Quantiles::finish`].
+    Exact(Vec<Sum>, bool),
+    Sketch(TDigest),
+}
+
+impl Quantiles {
+    pub fn new(mode: QuantileMode) -> Self {
+        Quantiles(match mode {
+            QuantileMode::Exact => Inner::Exact(Vec::new(), true),
+            QuantileMode::Sketch => Inner::Sketch(TDigest::default()),
+        })
+    }
+
+    pub fn add(&mut self, n: &Sum) {
+        match &mut self.0 {
+            Inner::Exact(values, sorted) => {
+                values.push(n.clone());
+                *sorted = false;
+            }
+            Inner::Sketch(digest) => digest.add(n.to_f64()),
+        }
+    }
+
+    /// Sorts or merges the values added so far, so that computing percentiles doesn't have to
+    /// copy them each time. More values can still be added afterwards.
+    pub fn finish(&mut self) {
+        match &mut self.0 {
+            Inner::Exact(values, sorted) => {
+                if !*sorted {
+                    values.sort_by(cmp);
+                    *sorted = true;
+                }
+            }
+            Inner::Sketch(digest) => digest.compress(),
+        }
+    }
+
+    /// Returns the `p`th percentile, where `p` is between 0 and 100, or `None` if no values were
+    /// added. Exact percentiles use the nearest-rank method, so they're always one of the input
+    /// values and keep its type. Sketched percentiles are interpolated Floats. Faster after
+    /// [`Quantiles::finish`].
+    pub fn percentile(&self, p: f64) -> Option<Sum> {
+        match &self.0 {
+            Inner::Exact(values, _) if values.is_empty() => None,
+            Inner::Exact(values, sorted) => {
+                let rank = (p * values.len() as f64 / 100.0).ceil() as usize;
+                let index = rank.clamp(1, values.len()) - 1;
+                if *sorted {
+                    return Some(values[index].clone());
+                }
+                let mut values = values.clone();
+                let (_, n, _) = values.select_nth_unstable_by(index, cmp);
+                Some(n.clone())
+            }
+            Inner::Sketch(digest) => digest.quantile(p / 100.0).map(Sum::Float),
+        }
+    }
+}
+
+/// Orders Sums by value, with NaN equal to everything.
+fn cmp(a: &Sum, b: &Sum) -> Ordering {
+    a.cmp_value(b).unwrap_or(Ordering::Equal)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// A merging t-digest (Dunning & Ertl), which estimates quantiles in bounded memory. Values are
+/// buffered and periodically merged into a small set of centroids that are finer near the tails,
+/// so extreme percentiles like p99.9 stay accurate.
+#[derive(Debug, Clone, PartialEq)]
+struct TDigest {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    buffer: Vec<f64>,
+    min: f64,
+    max: f64,
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        TDigest::new(200.0)
+    }
+}
+
+impl TDigest {
+    /// Creates an empty digest. Higher `compression` keeps more centroids, which is more
+    /// accurate but uses more memory.
+    fn new(compression: f64) -> Self {
+        TDigest {
+            compression,
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    fn add(&mut self, x: f64) {
+        if x.is_nan() {
+            return;
+        }
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+        self.buffer.push(x);
+        if self.buffer.len() >= 10 * self.compression as usize {
+            self.compress();
+        }
+    }
+
+    /// The scale function k1, which maps a quantile to a centroid index.
+    fn k(&self, q: f64) -> f64 {
+        self.compression / (2.0 * PI) * (2.0 * q.clamp(0.0, 1.0) - 1.0).asin()
+    }
+
+    /// The inverse of [`TDigest::k`].
+    fn k_inv(&self, k: f64) -> f64 {
+        ((2.0 * PI * k / self.compression).sin() + 1.0) / 2.0
+    }
+
+    /// Merges the buffered values into the centroids.
+    fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut all = std::mem::take(&mut self.centroids);
+        all.extend(
+            self.buffer
+                .drain(..)
+                .map(|mean| Centroid { mean, weight: 1.0 }),
+        );
+        all.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+        let total: f64 = all.iter().map(|c| c.weight).sum();
+
+        let mut merged = Vec::new();
+        let mut cur = all[0];
+        let mut weight_so_far = 0.0;
+        let mut limit = total * self.k_inv(self.k(0.0) + 1.0);
+        for c in all.into_iter().skip(1) {
+            if weight_so_far + cur.weight + c.weight <= limit {
+                let weight = cur.weight + c.weight;
+                cur.mean += (c.mean - cur.mean) * c.weight / weight;
+                cur.weight = weight;
+            } else {
+                weight_so_far += cur.weight;
+                merged.push(cur);
+                limit = total * self.k_inv(self.k(weight_so_far / total) + 1.0);
+                cur = c;
+            }
+        }
+        merged.push(cur);
+        self.centroids = merged;
+    }
+
+    /// Estimates the `q`th quantile, where `q` is between 0 and 1, or returns `None` if no
+    /// values were added.
+    fn quantile(&self, q: f64) -> Option<f64> {
+        let digest = match self.buffer.is_empty() {
+            true => Cow::Borrowed(self),
+            false => {
+                let mut digest = self.clone();
+                digest.compress();
+                Cow::Owned(digest)
+            }
+        };
+        let centroids = &digest.centroids;
+        let total: f64 = centroids.iter().map(|c| c.weight).sum();
+        let target = q.clamp(0.0, 1.0) * total;
+        let first = centroids.first()?;
+        if centroids.len() == 1 {
+            return Some(first.mean);
+        }
+
+        // Interpolate between the centers of adjacent centroids, using min and max as the
+        // outermost points.
+        let mut prev = (0.0, digest.min);
+        let mut cumulative = 0.0;
+        for c in centroids {
+            let center = cumulative + c.weight / 2.0;
+            if target < center {
+                let (prev_center, prev_mean) = prev;
+                let t = (target - prev_center) / (center - prev_center);
+                return Some(prev_mean + (c.mean - prev_mean) * t);
+            }
+            prev = (center, c.mean);
+            cumulative += c.weight;
+        }
+        let (prev_center, prev_mean) = prev;
+        let t = (target - prev_center) / (total - prev_center);
+        Some(prev_mean + (digest.max - prev_mean) * t.min(1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_nearest_rank() {
+        let mut q = Quantiles::new(QuantileMode::Exact);
+        assert_eq!(q.percentile(50.0), None);
+        for n in [5, 1, 4, 2, 3] {
+            q.add(&Sum::Integer(n));
+        }
+        assert_eq!(q.percentile(0.0), Some(Sum::Integer(1)));
+        assert_eq!(q.percentile(50.0), Some(Sum::Integer(3)));
+        assert_eq!(q.percentile(80.0), Some(Sum::Integer(4)));
+        assert_eq!(q.percentile(80.1), Some(Sum::Integer(5)));
+        assert_eq!(q.percentile(100.0), Some(Sum::Integer(5)));
+
+        // The same after sorting, and with values added afterwards.
+        q.finish();
+        assert_eq!(q.percentile(80.0), Some(Sum::Integer(4)));
+        q.add(&Sum::Integer(0));
+        assert_eq!(q.percentile(0.0), Some(Sum::Integer(0)));
+        q.finish();
+        assert_eq!(q.percentile(0.0), Some(Sum::Integer(0)));
+        assert_eq!(q.percentile(50.0), Some(Sum::Integer(2)));
+    }
+
+    #[test]
+    fn sketch_is_close() {
+        let mut q = Quantiles::new(QuantileMode::Sketch);
+        assert_eq!(q.percentile(50.0), None);
+        for n in 1..=100_000 {
+            q.add(&Sum::Integer(n));
+        }
+        for (p, expected) in [
+            (0.0, 1.0),
+            (50.0, 50_000.0),
+            (99.9, 99_900.0),
+            (100.0, 100_000.0),
+        ] {
+            let Some(Sum::Float(actual)) = q.percentile(p) else {
+                panic!("expected a Float for p{p}");
+            };
+            assert!(
+                (actual - expected).abs() <= 100.0,
+                "p{p}: {actual} != {expected}"
+            );
+        }
+        let Inner::Sketch(digest) = &q.0 else {
+            unreachable!()
+        };
+        assert!(digest.centroids.len() + digest.buffer.len() < 3000);
+    }
+
+    #[test]
+    fn sketch_single_value() {
+        let mut digest = TDigest::default();
+        digest.add(42.0);
+        assert_eq!(digest.quantile(0.5), Some(42.0));
+    }
+
+    #[test]
+    fn parse_mode_works() {
+        assert_eq!("sketch".parse(), Ok(QuantileMode::Sketch));
+        assert!("tdigest".parse::<QuantileMode>().is_err());
+        for mode in [QuantileMode::Exact, QuantileMode::Sketch] {
+            assert_eq!(mode.to_string().parse(), Ok(mode));
+        }
+    }
+}
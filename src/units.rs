@@ -0,0 +1,356 @@
+use crate::{Decimal, Sum};
+use std::fmt;
+use std::str::FromStr;
+
+/// The units of the input, which are converted to a base unit when parsed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Units {
+    /// Sizes, converted to bytes. Accepts SI suffixes like `20kB` and `1.5GB`, IEC suffixes like
+    /// `512Mi` and `1KiB`, and single letters like `1.5G`, which are binary as in `du -h`.
+    Size,
+    /// Durations, converted to seconds. Accepts units like `250ms`, `1.5s` and `2h3m4s`, clock
+    /// times like `01:02:03` and `2:03` (minutes and seconds), and plain seconds. Decimal values
+    /// are always summed exactly.
+    Duration,
+}
+
+/// How to print sizes in human-readable form.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SizeFormat {
+    /// Human-readable with binary (IEC) suffixes, like 1.5GiB.
+    Binary,
+    /// Human-readable with decimal (SI) suffixes, like 1.6GB.
+    Decimal,
+}
+
+/// How to print durations.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum DurationFormat {
+    /// Days, hours, minutes and seconds, like 1d2h3m4.5s (default).
+    #[default]
+    Compact,
+    /// Hours, minutes and seconds, like 26:03:04.5.
+    Clock,
+    /// A plain number of seconds, like 93784.5.
+    Seconds,
+}
+
+/// The prefixes for each power of 1000 or 1024.
+const PREFIXES: [char; 6] = ['K', 'M', 'G', 'T', 'P', 'E'];
+
+impl Units {
+    /// Splits `s` into numbers, each with the value of its unit in the base unit, which add up to
+    /// the value of `s`. Returns `None` if it has an unknown unit.
+    pub fn split(self, s: &str) -> Option<Vec<(&str, Sum)>> {
+        match self {
+            Units::Size => split_size(s).map(|part| vec![part]),
+            Units::Duration => split_duration(s),
+        }
+    }
+}
+
+fn split_size(s: &str) -> Option<(&str, Sum)> {
+    if s.starts_with("0x") {
+        return Some((s, Sum::Integer(1)));
+    }
+    let number = s.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let suffix = &s[number.len()..];
+    let factor = match suffix.chars().next() {
+        None => 1,
+        Some(_) if suffix == "B" => 1,
+        Some(prefix) => {
+            let power = PREFIXES
+                .iter()
+                .position(|p| p.eq_ignore_ascii_case(&prefix))?;
+            let base: i128 = match &suffix[1..] {
+                "" | "i" | "iB" => 1024,
+                "B" => 1000,
+                _ => return None,
+            };
+            base.pow(power as u32 + 1)
+        }
+    };
+    Some((number.trim_end(), Sum::Integer(factor)))
+}
+
+fn split_duration(s: &str) -> Option<Vec<(&str, Sum)>> {
+    // A leading sign applies to the whole duration, so `-1h30m` is -5400 seconds.
+    let (sign, s) = match s.strip_prefix('-') {
+        Some(s) => (-1, s),
+        None => (1, s),
+    };
+    let seconds = |unscaled: i128, scale: u32| match scale {
+        0 => Sum::Integer(sign * unscaled),
+        _ => Sum::Decimal(Decimal::new(sign * unscaled, scale)),
+    };
+    if s.contains(':') {
+        let parts: Vec<&str> = s.split(':').collect();
+        let factors: &[i128] = match parts.len() {
+            2 => &[60, 1],
+            3 => &[3600, 60, 1],
+            _ => return None,
+        };
+        return Some(
+            parts
+                .into_iter()
+                .zip(factors)
+                .map(|(part, &factor)| (part, seconds(factor, 0)))
+                .collect(),
+        );
+    }
+    let mut parts = Vec::new();
+    let mut rest = s;
+    while !rest.is_empty() {
+        let number_end = rest.find(char::is_alphabetic).unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(number_end);
+        let unit_end = tail
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_end);
+        let factor = match unit {
+            // A plain number of seconds.
+            "" if parts.is_empty() => seconds(1, 0),
+            "d" => seconds(86400, 0),
+            "h" => seconds(3600, 0),
+            "m" => seconds(60, 0),
+            "s" => seconds(1, 0),
+            "ms" => seconds(1, 3),
+            "us" | "µs" => seconds(1, 6),
+            "ns" => seconds(1, 9),
+            _ => return None,
+        };
+        parts.push((number, factor));
+        rest = tail;
+    }
+    Some(parts)
+}
+
+impl SizeFormat {
+    /// Formats `n` bytes, like `1.5GiB` for binary sizes, rounded to one decimal place.
+    pub fn format(self, n: &Sum) -> String {
+        let (base, suffix) = match self {
+            SizeFormat::Binary => (1024.0, "iB"),
+            SizeFormat::Decimal => (1000.0, "B"),
+        };
+        let mut value = n.to_f64();
+        let mut prefix = None;
+        for p in PREFIXES {
+            if value.abs() < base {
+                break;
+            }
+            value /= base;
+            prefix = Some(p);
+        }
+        let Some(prefix) = prefix else {
+            return format!("{n}B");
+        };
+        let prefix = match (self, prefix) {
+            (SizeFormat::Decimal, 'K') => 'k',
+            (_, prefix) => prefix,
+        };
+        let value = format!("{value:.1}");
+        let value = value.strip_suffix(".0").unwrap_or(&value);
+        format!("{value}{prefix}{suffix}")
+    }
+}
+
+impl DurationFormat {
+    /// Formats `n` seconds, keeping any fraction of a second as it is.
+    pub fn format(self, n: &Sum) -> String {
+        if self == DurationFormat::Seconds {
+            return n.to_string();
+        }
+        let s = n.to_string();
+        let (sign, s) = match s.strip_prefix('-') {
+            Some(s) => ("-", s),
+            None => ("", s.as_str()),
+        };
+        let (secs, frac) = s.split_once('.').unwrap_or((s, ""));
+        let frac = match frac.trim_end_matches('0') {
+            "" => String::new(),
+            frac => format!(".{frac}"),
+        };
+        // Too large (or not a number) to split up, so leave it as seconds.
+        let Ok(secs) = secs.parse::<u128>() else {
+            return n.to_string();
+        };
+        let (d, h, m, s) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+        if self == DurationFormat::Clock {
+            return format!("{sign}{:02}:{m:02}:{s:02}{frac}", secs / 3600);
+        }
+        let mut out = sign.to_string();
+        for (value, unit) in [(d, "d"), (h, "h"), (m, "m")] {
+            if value > 0 {
+                out += &format!("{value}{unit}");
+            }
+        }
+        if s > 0 || !frac.is_empty() || out.len() == sign.len() {
+            out += &format!("{s}{frac}s");
+        }
+        out
+    }
+}
+
+impl FromStr for Units {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "size" => Ok(Units::Size),
+            "duration" => Ok(Units::Duration),
+            _ => Err(format!("unknown units {s:?} (expected size or duration)")),
+        }
+    }
+}
+
+impl fmt::Display for Units {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Units::Size => write!(f, "size"),
+            Units::Duration => write!(f, "duration"),
+        }
+    }
+}
+
+impl FromStr for SizeFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "binary" => Ok(SizeFormat::Binary),
+            "decimal" => Ok(SizeFormat::Decimal),
+            _ => Err(format!(
+                "unknown size format {s:?} (expected binary or decimal)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SizeFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SizeFormat::Binary => write!(f, "binary"),
+            SizeFormat::Decimal => write!(f, "decimal"),
+        }
+    }
+}
+
+impl FromStr for DurationFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "compact" => Ok(DurationFormat::Compact),
+            "clock" => Ok(DurationFormat::Clock),
+            "seconds" => Ok(DurationFormat::Seconds),
+            _ => Err(format!(
+                "unknown duration format {s:?} (expected one of compact, clock, seconds)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for DurationFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DurationFormat::Compact => write!(f, "compact"),
+            DurationFormat::Clock => write!(f, "clock"),
+            DurationFormat::Seconds => write!(f, "seconds"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_size_works() {
+        let split = |s| Units::Size.split(s);
+        assert_eq!(split("42"), Some(vec![("42", Sum::Integer(1))]));
+        assert_eq!(split("42B"), Some(vec![("42", Sum::Integer(1))]));
+        assert_eq!(split("20kB"), Some(vec![("20", Sum::Integer(1000))]));
+        assert_eq!(split("1.5G"), Some(vec![("1.5", Sum::Integer(1 << 30))]));
+        assert_eq!(split("512Mi"), Some(vec![("512", Sum::Integer(1 << 20))]));
+        assert_eq!(split("1 KiB"), Some(vec![("1", Sum::Integer(1024))]));
+        assert_eq!(
+            split("3EB"),
+            Some(vec![("3", Sum::Integer(10i128.pow(18)))])
+        );
+        assert_eq!(split("0x1B"), Some(vec![("0x1B", Sum::Integer(1))]));
+        assert_eq!(split("5 apples"), None);
+        assert_eq!(split("5Kb"), None);
+    }
+
+    #[test]
+    fn split_duration_works() {
+        let split = |s| Units::Duration.split(s);
+        let ms = Sum::Decimal(Decimal::new(1, 3));
+        assert_eq!(split("250ms"), Some(vec![("250", ms)]));
+        assert_eq!(split("1.5"), Some(vec![("1.5", Sum::Integer(1))]));
+        assert_eq!(
+            split("2h3m4.5s"),
+            Some(vec![
+                ("2", Sum::Integer(3600)),
+                ("3", Sum::Integer(60)),
+                ("4.5", Sum::Integer(1))
+            ])
+        );
+        assert_eq!(
+            split("-01:02"),
+            Some(vec![("01", Sum::Integer(-60)), ("02", Sum::Integer(-1))])
+        );
+        assert_eq!(split("1:2:3:4"), None);
+        assert_eq!(split("1h2"), None);
+        assert_eq!(split("3 weeks"), None);
+    }
+
+    #[test]
+    fn format_size_works() {
+        let n = Sum::Integer(1_610_612_736);
+        assert_eq!(SizeFormat::Binary.format(&n), "1.5GiB");
+        assert_eq!(SizeFormat::Decimal.format(&n), "1.6GB");
+        assert_eq!(SizeFormat::Decimal.format(&Sum::Integer(20_000)), "20kB");
+        assert_eq!(SizeFormat::Binary.format(&Sum::Integer(512)), "512B");
+        assert_eq!(SizeFormat::Binary.format(&Sum::Integer(-2048)), "-2KiB");
+    }
+
+    #[test]
+    fn format_duration_works() {
+        let n = Sum::Decimal(Decimal::new(937_845, 1));
+        assert_eq!(DurationFormat::Compact.format(&n), "1d2h3m4.5s");
+        assert_eq!(DurationFormat::Clock.format(&n), "26:03:04.5");
+        assert_eq!(DurationFormat::Seconds.format(&n), "93784.5");
+        assert_eq!(DurationFormat::Compact.format(&Sum::Integer(3600)), "1h");
+        assert_eq!(DurationFormat::Compact.format(&Sum::Integer(0)), "0s");
+        assert_eq!(
+            DurationFormat::Compact.format(&Sum::Decimal(Decimal::new(-2500, 3))),
+            "-2.5s"
+        );
+        assert_eq!(DurationFormat::Clock.format(&Sum::Integer(59)), "00:00:59");
+        assert_eq!(DurationFormat::Compact.format(&Sum::Float(1.5)), "1.5s");
+    }
+
+    #[test]
+    fn parse_works() {
+        assert_eq!("duration".parse(), Ok(Units::Duration));
+        assert_eq!("decimal".parse(), Ok(SizeFormat::Decimal));
+        assert_eq!("clock".parse(), Ok(DurationFormat::Clock));
+        assert!("bytes".parse::<Units>().is_err());
+        assert!("si".parse::<SizeFormat>().is_err());
+        assert!("hms".parse::<DurationFormat>().is_err());
+        for units in [Units::Size, Units::Duration] {
+            assert_eq!(units.to_string().parse(), Ok(units));
+        }
+        for format in [SizeFormat::Binary, SizeFormat::Decimal] {
+            assert_eq!(format.to_string().parse(), Ok(format));
+        }
+        for format in [
+            DurationFormat::Compact,
+            DurationFormat::Clock,
+            DurationFormat::Seconds,
+        ] {
+            assert_eq!(format.to_string().parse(), Ok(format));
+        }
+    }
+}
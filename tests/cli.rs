@@ -124,6 +124,153 @@ fn sum_comma_numbers() -> TestResult {
     Ok(())
 }
 
+#[test]
+fn sum_locale_numbers() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("1.234,56\n0,44\n")
+        .args(["--locale", "de-DE", "--exact"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("1235.00\n"))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("1'234.50\n1'000\n")
+        .args(["--locale", "de_CH.UTF-8"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("2234.5\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("1 234,5\n1\u{202f}000,5\n")
+        .args(["--decimal-sep", ",", "--group-sep", " "])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("2235\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("1,5\n2,5\n")
+        .args(["--decimal-sep", ","])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("4\n"))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("total 1.234,5 EUR\n")
+        .args(["--all-numbers", "--locale", "de-DE"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("1234.5\n"));
+    Ok(())
+}
+
+#[test]
+fn sum_accounting_notation() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("$1000.00\n(234.50)\n10.25-\n5 EUR\n")
+        .arg("--exact")
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("760.25\n"))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("(€5)\n")
+        .arg("-v")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#"note="Read parentheses as negative, Stripped currency""#,
+        ));
+    Ok(())
+}
+
+#[test]
+fn sum_unknown_locale() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("1\n")
+        .args(["--locale", "xx"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(r#"unknown locale "xx""#));
+    Ok(())
+}
+
+#[test]
+fn sum_octal_and_binary() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("755\n644\n")
+        .arg("--radix=octal")
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("0o1621\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("0b1010\n0o17\n0x10\n1\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("42\n"))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("0b11\n102\n")
+        .arg("--radix=binary")
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("0b11\n"))
+        .stderr(predicate::str::contains(
+            "Failed to parse as binary, treating as 0",
+        ));
+    Ok(())
+}
+
+#[test]
+fn sum_output_radix() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("0x1f\n0x10\n")
+        .args(["--output-radix", "hex"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("0x2f\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("1f\n10\n")
+        .args(["--radix", "hex", "--output-radix", "dec"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("47\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("0x1f\n0x10\n")
+        .args(["--output-radix=HEX", "--width=10", "--zero-pad"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("0x0000002F\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("5\n7\n")
+        .args(["--output-radix=bin", "--width=8"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("  0b1100\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("-5\n")
+        .args(["--width=5", "--zero-pad"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("-0005\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("-255\n")
+        .args(["--output-radix=hex", "--width=8", "--zero-pad"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("-0x000ff\n"));
+    Ok(())
+}
+
 #[test]
 fn sum_invalid_0x_prefix() -> TestResult {
     let mut cmd = Command::cargo_bin("sumcol")?;
@@ -155,15 +302,53 @@ fn sum_field_out_of_range_no_double_warn() -> TestResult {
 }
 
 #[test]
-fn sum_large_integer_warns() -> TestResult {
+fn sum_large_integers_exact() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    // i128::MAX is 170141183460469231731687303715884105727, so these overflow
+    cmd.write_stdin(
+        "170141183460469231731687303715884105727\n999999999999999999999999999999999999999999\n1\n",
+    )
+    .assert()
+    .success()
+    .stdout(predicate::str::diff(
+        "1000170141183460469231731687303715884105727\n",
+    ))
+    .stderr(predicate::str::is_empty());
+    Ok(())
+}
+
+#[test]
+fn sum_exact_decimals() -> TestResult {
     let mut cmd = Command::cargo_bin("sumcol")?;
-    // i128::MAX is 170141183460469231731687303715884105727, so one digit more overflows
-    cmd.write_stdin("999999999999999999999999999999999999999999\n")
+    cmd.write_stdin("0.1\n".repeat(10))
+        .args(["--exact"])
         .assert()
         .success()
-        .stderr(predicate::str::contains(
-            "Value too large for integer, using float (may lose precision)",
-        ));
+        .stdout(predicate::str::diff("1.0\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("1.10\n2.20\n")
+        .args(["--exact"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("3.30\n"));
+    Ok(())
+}
+
+#[test]
+fn sum_precise_float() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("0.1\n".repeat(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("0.9999999999999999\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("0.1\n".repeat(10))
+        .args(["--precise-float"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("1\n"));
     Ok(())
 }
 
@@ -260,6 +445,516 @@ fn sum_first_delimiter() -> TestResult {
     Ok(())
 }
 
+#[test]
+fn sum_multiple_fields() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    let input = r"
+    a 1 2 3 4
+    b 10 20 30 40
+    c 100
+    ";
+    cmd.write_stdin(input)
+        .args(["-f2,4-5"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("111\t33\t44\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin(input)
+        .args(["-f2", "-f4", "--agg=sum,max"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("sum\t111\t33\nmax\t100\t30\n"));
+    Ok(())
+}
+
+#[test]
+fn sum_invalid_field_range() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.args(["-f5-2"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("5 is after 2"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.args(["-f1-100000000"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("more than 10000 fields"));
+    Ok(())
+}
+
+#[test]
+fn sum_group_by() -> TestResult {
+    let input = "b 1\na 3\nb 5\nc 2\nd\n";
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin(input)
+        .args(["--group-by=1", "-f2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("b\t6\na\t3\nc\t2\nd\t0\n"))
+        .stderr(predicate::str::contains(
+            "Field index out of range, skipping",
+        ));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin(input)
+        .args(["-g1", "-f2", "--sort=key"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("a\t3\nb\t6\nc\t2\nd\t0\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin(input)
+        .args(["-g1", "-f2", "--top=2", "--agg=sum,count"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("b\t6\t2\na\t3\t1\n"));
+    Ok(())
+}
+
+#[test]
+fn sum_header_names() -> TestResult {
+    let input = "USER PID RSS\nroot 1 100\nme 2 250\nroot 3 50\n";
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin(input)
+        .args(["--header", "-f", "RSS"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("400\n"))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin(input)
+        .args(["--header", "-f", "RSS,2", "-g", "USER"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("root\t150\t4\nme\t250\t2\n"));
+
+    // Column names that look like indexes or ranges are still names.
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("a 2019 10-20\n1 2 3\n4 5 6\n")
+        .args(["--header", "-f", "2019,10-20,1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("7\t9\t5\n"))
+        .stderr(predicate::str::is_empty());
+    Ok(())
+}
+
+#[test]
+fn sum_header_skipped_in_each_file() -> TestResult {
+    let mut file1 = tempfile::NamedTempFile::new()?;
+    writeln!(file1, "n\n1\n2")?;
+    let mut file2 = tempfile::NamedTempFile::new()?;
+    writeln!(file2, "n\n3")?;
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.args(["--header", "-f", "n"])
+        .args([file1.path(), file2.path()])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("6\n"))
+        .stderr(predicate::str::is_empty());
+    Ok(())
+}
+
+#[test]
+fn sum_strict() -> TestResult {
+    let mut file = tempfile::NamedTempFile::new()?;
+    writeln!(file, "n\n1\nOOPS\n3")?;
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.args(["--strict", "--header", "-f", "n"])
+        .arg(file.path())
+        .assert()
+        .code(65)
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(format!(
+            "{}:3: failed to parse \"OOPS\"",
+            file.path().display()
+        )));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("1\n2\n")
+        .arg("--strict")
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("3\n"));
+    Ok(())
+}
+
+#[test]
+fn sum_strict_skipped() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("{\"a\": 1}\nnot json\n{\"a\": 2}\n")
+        .args(["--json", "-f", ".a", "--strict"])
+        .assert()
+        .code(65)
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("<stdin>:2: skipped \"not json\""));
+
+    // Lines are counted from where each record starts, even when fields contain newlines.
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("n,note\n1,\"two\nlines\"\nOOPS,x\n")
+        .args(["--csv", "--header", "-f", "n", "--strict"])
+        .assert()
+        .code(65)
+        .stderr(predicate::str::contains(
+            "<stdin>:4: failed to parse \"OOPS\"",
+        ));
+    Ok(())
+}
+
+#[test]
+fn sum_max_errors() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("1\nx\n2\n")
+        .args(["--max-errors", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("3\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("1\nx\ny\n")
+        .args(["--max-errors", "1"])
+        .assert()
+        .code(65)
+        .stderr(predicate::str::contains(
+            "<stdin>:3: failed to parse \"y\" (more than --max-errors 1)",
+        ));
+    Ok(())
+}
+
+#[test]
+fn sum_warnings_summary() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("1\nOOPS\nx\ny\nz\n1,000\n")
+        .arg("--warnings=summary")
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("1001\n"))
+        .stderr(predicate::str::diff(
+            "warning: problems with the input (use --warnings=each to see every one):
+  4 failed to parse, counted as 0: \"OOPS\", \"x\", \"y\", ...
+  1 had commas stripped (use --locale if they're decimal points): \"1,000\"
+",
+        ));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("1\n2\n")
+        .arg("--warnings=summary")
+        .assert()
+        .success()
+        .stderr(predicate::str::is_empty());
+    Ok(())
+}
+
+#[test]
+fn sum_per_file() -> TestResult {
+    let mut file1 = tempfile::NamedTempFile::new()?;
+    writeln!(file1, "1\n2")?;
+    let mut file2 = tempfile::NamedTempFile::new()?;
+    writeln!(file2, "3\nx\n")?;
+    let (path1, path2) = (file1.path(), file2.path());
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.arg("--per-file")
+        .args([path1, path2])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(format!(
+            "3\t2\t0\t{}\n3\t3\t1\t{}\n6\t5\t1\ttotal\n",
+            path1.display(),
+            path2.display()
+        )));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.args(["--per-file", "--agg", "sum,max"])
+        .arg(path1)
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(format!(
+            "3\t2\t2\t0\t{}\n3\t2\t2\t0\ttotal\n",
+            path1.display()
+        )));
+    Ok(())
+}
+
+#[test]
+fn sum_running() -> TestResult {
+    let mut file = tempfile::NamedTempFile::new()?;
+    writeln!(file, "a 1\nb\nc 2\nd 0x10")?;
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.args(["-f2", "--running"])
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("a 1\t1\nb\t1\nc 2\t3\nd 0x10\t19\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.args(["-f2", "--running=totals", "--output-radix=hex"])
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("0x1\n0x3\n0x13\n"));
+    Ok(())
+}
+
+/// Kills the child process when dropped, so a failed assertion doesn't leave it running.
+struct KillOnDrop(std::process::Child);
+
+impl Drop for KillOnDrop {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+#[test]
+fn sum_follow() -> TestResult {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+    let mut file = tempfile::NamedTempFile::new()?;
+    writeln!(file, "1\n2")?;
+    let mut child = KillOnDrop(
+        std::process::Command::new(assert_cmd::cargo::cargo_bin("sumcol"))
+            .arg("--follow")
+            .arg(file.path())
+            .stdout(Stdio::piped())
+            .spawn()?,
+    );
+    // Read on another thread, since reading from the pipe can't time out.
+    let stdout = BufReader::new(child.0.stdout.take().ok_or("no stdout")?);
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in stdout.lines() {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    assert_eq!(rx.recv_timeout(TIMEOUT)??, "3");
+
+    writeln!(file, "4")?;
+    assert_eq!(rx.recv_timeout(TIMEOUT)??, "7");
+    Ok(())
+}
+
+#[test]
+fn sum_follow_single_file() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.args(["--follow", "a", "b"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--follow takes a single file"));
+    Ok(())
+}
+
+#[test]
+fn sum_follow_zero_interval() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.args(["--follow", "--interval", "0", "a"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("must be more than 0"));
+    Ok(())
+}
+
+#[test]
+fn sum_header_unknown_name() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("USER PID RSS\nroot 1 100\n")
+        .args(["--header", "-f", "VSZ"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            r#"unknown field "VSZ" (available columns: USER, PID, RSS)"#,
+        ));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("USER PID RSS\nroot 1 100\n")
+        .args(["-f", "RSS"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("requires --header"));
+    Ok(())
+}
+
+#[test]
+fn sum_csv_quoted_fields() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("\"Amount\",\"Name\"\n\"1,234\",\"Acme, Inc.\"\n55,\"Two\nLines\"\n")
+        .args(["--csv", "--header", "-f", "Amount"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("1289\n"));
+    Ok(())
+}
+
+#[test]
+fn sum_tsv() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("a b\t1\nc d\t2\n")
+        .args(["--tsv", "-f2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("3\n"));
+    Ok(())
+}
+
+#[test]
+fn sum_extract() -> TestResult {
+    let input = "GET /a took 123ms status=200\nstarting up\nGET /b took 7ms status=500\n";
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin(input)
+        .args(["--extract", r"took (\d+)ms"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("130\n"))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin(input)
+        .args(["-e", r"(\w+) took (?P<ms>\d+)ms", "-g2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("/a\t123\n/b\t7\n"));
+    Ok(())
+}
+
+#[test]
+fn sum_all_numbers() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("3 apples and 4 pears\nno fruit\n0x10 plums, -2.5 figs\n")
+        .args(["--all-numbers"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("20.5\n"))
+        .stderr(predicate::str::is_empty());
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("GET took 12ms size 3\nGET took 8ms size 4\n")
+        .args(["--all-numbers", "-e", "took (.*)"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("27\n"));
+    Ok(())
+}
+
+#[test]
+fn sum_units_size() -> TestResult {
+    let input = "1.5G\tlogs\n512Mi\tcache\n20kB\tconfig\n100\tREADME\n";
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin(input)
+        .args(["--units=size", "-f1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("2147503748\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin(input)
+        .args(["--units=size", "--size-format=binary", "-f1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("2GiB\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin(input)
+        .args([
+            "--units=size",
+            "--size-format=decimal",
+            "-f1",
+            "--agg=max,count",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("max\t1.6GB\ncount\t4\n"));
+    Ok(())
+}
+
+#[test]
+fn sum_units_size_unknown_suffix() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("1K\n5 apples\n")
+        .args(["--units=size"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("1024\n"))
+        .stderr(predicate::str::contains("Failed to parse"));
+    Ok(())
+}
+
+#[test]
+fn sum_units_duration() -> TestResult {
+    let input = "build 1m30s\ntest 250ms\nlint 0.1\ndeploy 01:02:03\nfailed ?\n";
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin(input)
+        .args(["--units=duration", "-f2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("1h3m33.35s\n"))
+        .stderr(predicate::str::contains("Failed to parse"));
+
+    for (format, expected) in [("clock", "01:03:33.35\n"), ("seconds", "3813.350\n")] {
+        let mut cmd = Command::cargo_bin("sumcol")?;
+        cmd.write_stdin(input)
+            .args(["--units=duration", "-f2", "--duration-format", format])
+            .assert()
+            .success()
+            .stdout(predicate::str::diff(expected));
+    }
+    Ok(())
+}
+
+#[test]
+fn sum_json_paths() -> TestResult {
+    let input = r#"{"response": {"bytes": 100}, "items": [{"price": 1.25}, {"price": 2}]}
+{"response": {"bytes": 50}, "items": [{"price": "0.75"}]}
+{"response": {"bytes": null}, "items": []}
+"#;
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin(input)
+        .args(["--json", "-f", ".response.bytes,.items[].price"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("150\t4\n"))
+        .stderr(predicate::str::contains("Value is not a number, skipping"))
+        .stderr(predicate::str::contains("Field not found, skipping"));
+    Ok(())
+}
+
+#[test]
+fn sum_json_group_by() -> TestResult {
+    let input = r#"{"host": "a", "bytes": 1}
+{"host": "b", "bytes": 2}
+not json
+{"host": "a", "bytes": 3}
+"#;
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin(input)
+        .args(["--json", "-f", ".bytes", "-g", ".host"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("a\t4\nb\t2\n"))
+        .stderr(predicate::str::contains("Invalid JSON, skipping"));
+    Ok(())
+}
+
+#[test]
+fn sum_json_path_requires_json() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("1\n")
+        .args(["-f", ".bytes"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            r#"field ".bytes" is a JSON path, which requires --json"#,
+        ));
+    Ok(())
+}
+
 #[test]
 fn sum_mixed_column() -> TestResult {
     let mut cmd = Command::cargo_bin("sumcol")?;
@@ -372,6 +1067,91 @@ fn sum_float_hex_flag() -> TestResult {
     Ok(())
 }
 
+#[test]
+fn agg_single() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("3\n1\n2\n")
+        .args(["--agg=max"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("3\n"));
+
+    // NaN is left out of the min and max.
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("NaN\n1\n3\n")
+        .args(["--agg=min,max"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("min\t1\nmax\t3\n"));
+    Ok(())
+}
+
+#[test]
+fn agg_multiple() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("3\n1.5\nOOPS\n2\n")
+        .args(["--agg=count,mean", "--agg=min", "--agg", "max", "--agg=sum"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(
+            "count\t3\nmean\t2.1666666666666665\nmin\t1.5\nmax\t3\nsum\t6.5\n",
+        ));
+    Ok(())
+}
+
+#[test]
+fn agg_percentiles() -> TestResult {
+    let input: String = (1..=1000).map(|n| format!("{n}\n")).collect();
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin(input.clone())
+        .args(["--agg=median,p90,p99.9"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("median\t500\np90\t900\np99.9\t999\n"));
+
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin(input)
+        .args(["--agg=p50", "--percentiles=sketch"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("500.5\n"));
+    Ok(())
+}
+
+#[test]
+fn agg_stddev() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("2\n4\n4\n4\n5\n5\n7\n9\n")
+        .args(["--agg=pstddev,pvariance,variance"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff(
+            "pstddev\t2\npvariance\t4\nvariance\t4.571428571428571\n",
+        ));
+    Ok(())
+}
+
+#[test]
+fn agg_empty_input() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.write_stdin("")
+        .args(["--agg=count,mean"])
+        .assert()
+        .success()
+        .stdout(predicate::str::diff("count\t0\nmean\t-\n"));
+    Ok(())
+}
+
+#[test]
+fn agg_unknown() -> TestResult {
+    let mut cmd = Command::cargo_bin("sumcol")?;
+    cmd.args(["--agg=bogus"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown aggregation"));
+    Ok(())
+}
+
 #[test]
 fn sum_nonexistent_file() -> TestResult {
     let mut cmd = Command::cargo_bin("sumcol")?;